@@ -0,0 +1,38 @@
+/// The lookup table of the CRC-32 (IEEE) polynomial in reversed form.
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+
+        let mut j = 0;
+
+        while j < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+
+            j += 1;
+        }
+
+        table[i] = crc;
+
+        i += 1;
+    }
+
+    table
+}
+
+/// Continue the CRC-32 checksum `crc` over `bytes`. The checksum of no data is `0`.
+#[inline]
+pub(crate) fn update(crc: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !crc;
+
+    for &b in bytes {
+        crc = TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+
+    !crc
+}
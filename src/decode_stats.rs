@@ -0,0 +1,11 @@
+use std::time::Duration;
+
+/// The amount of data decoded by a reader and the time spent in the base64 engine, without the
+/// time spent reading from the inner reader.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// The number of decoded bytes.
+    pub bytes: u64,
+    /// The time spent decoding.
+    pub elapsed: Duration,
+}
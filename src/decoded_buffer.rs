@@ -0,0 +1,93 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::HexPreview;
+
+/// Plain data decoded ahead of the caller, which backs `fill_buf` and `peek`. The blocking and
+/// the asynchronous readers share it, so that they stage decoded data the same way.
+#[derive(Clone, Default)]
+pub(crate) struct DecodedBuffer {
+    pub(crate) buf: Vec<u8>,
+    pub(crate) offset: usize,
+    pub(crate) length: usize,
+}
+
+impl DecodedBuffer {
+    /// The decoded data which has not been consumed yet.
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.buf[self.offset..self.length]
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.length - self.offset
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset >= self.length
+    }
+
+    #[inline]
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.offset = (self.offset + amt).min(self.length);
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.offset = 0;
+        self.length = 0;
+    }
+
+    /// Copy as much of the unconsumed data as fits into `buf` and consume it.
+    #[inline]
+    pub(crate) fn drain_into(&mut self, buf: &mut [u8]) -> usize {
+        let decoded = self.as_slice();
+
+        let drain_length = decoded.len().min(buf.len());
+
+        buf[..drain_length].copy_from_slice(&decoded[..drain_length]);
+
+        self.consume(drain_length);
+
+        drain_length
+    }
+
+    /// Discard the consumed data and return the whole buffer to decode into, allocating
+    /// `capacity` bytes the first time. Pass the decoded length to `filled` afterwards.
+    #[inline]
+    pub(crate) fn refill(&mut self, capacity: usize) -> &mut [u8] {
+        debug_assert!(self.is_empty());
+
+        if self.buf.is_empty() {
+            self.buf.resize(capacity, 0);
+        }
+
+        self.clear();
+
+        &mut self.buf
+    }
+
+    #[inline]
+    pub(crate) fn filled(&mut self, length: usize) {
+        debug_assert!(length <= self.buf.len());
+
+        self.offset = 0;
+        self.length = length;
+    }
+}
+
+/// Only the unconsumed data are shown, not the whole buffer.
+impl Debug for DecodedBuffer {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        Debug::fmt(&HexPreview(self.as_slice()), f)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for DecodedBuffer {
+    #[inline]
+    fn drop(&mut self) {
+        crate::wipe(&mut self.buf);
+    }
+}
@@ -0,0 +1,1142 @@
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::io;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::task::Poll;
+#[cfg(feature = "metrics")]
+use std::time::Duration;
+
+use core::fmt::{self, Debug, Formatter};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::{ArrayLength, GenericArray};
+
+use base64::{self,
+    Engine,
+    DecodeError,
+};
+
+use crate::{DecoderError, FromBase64Error, HexPreview};
+
+/// The progress of skipping a `data:...;base64,` prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DataUri {
+    /// Checking whether the stream starts with `data:`.
+    Scheme,
+    /// Skipping the media type and parameters up to the comma.
+    Header,
+    /// Decoding base64 data.
+    Done,
+}
+
+/// The decoding state shared by the blocking and the asynchronous base64 readers. It does not
+/// own the inner reader; callers read base64 data into `spare` and report it with `fill`.
+///
+/// It needs no `std`, so it can also be driven manually in `no_std` environments: while
+/// `needs_data` returns `true`, read base64 data into `spare` and pass the length to `fill`, then
+/// call `decode`. Once the base64 data runs out, call `decode_end` until it returns `0`. Data
+/// which is already in memory can be passed to `feed` instead of `spare` and `fill`.
+#[derive(Educe)]
+#[educe(Clone)]
+pub struct Decoder<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096> {
+    buf: GenericArray<u8, N>,
+    buf_length: usize,
+    buf_offset: usize,
+    temp: [u8; 2],
+    temp_length: usize,
+    /// The engine, copied so that one built at run time, e.g. from a decode table, can be used.
+    pub(crate) engine: base64::engine::general_purpose::GeneralPurpose,
+    pub(crate) ignore_whitespace: bool,
+    /// Whether a UTF-8 BOM may still start the stream, which is skipped if whitespaces are
+    /// ignored.
+    bom_pending: bool,
+    pub(crate) ignore_invalid: bool,
+    pub(crate) detect_alphabet: bool,
+    detected_url_safe: Option<bool>,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    strip_data_uri: bool,
+    data_uri: DataUri,
+    data_uri_tail: [u8; 7],
+    pub(crate) limit: Option<u64>,
+    /// The largest `spare` slice handed out, to bound each read from the inner reader.
+    pub(crate) max_inner_read: Option<usize>,
+    /// Whether trailing NULs end the base64 data, and whether one has been met.
+    pub(crate) nul_padded: bool,
+    nul_reached: bool,
+    /// Whether the first padded block ends the data, whether it has been met, and the number of
+    /// bytes read after it, which are kept after the buffered data for the caller.
+    pub(crate) stop_at_padding: bool,
+    padding_reached: bool,
+    trailing_length: usize,
+    /// The padding character of the stream, which is swapped with `=` as the data is filled in.
+    pub(crate) pad: u8,
+    /// The expected and the running CRC-32 checksum of the decoded data.
+    pub(crate) crc32: Option<(u32, u32)>,
+    /// The stream offset of the first `=` and the bytes of the padded block which has been
+    /// decoded, after which only the end of the stream may follow.
+    padded_block: Option<(u64, [u8; 4])>,
+    /// Whether the stream ended on a complete block, once it has ended.
+    ended_cleanly: Option<bool>,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) skip_bytes: u64,
+    skip_remaining: u64,
+    pub(crate) decoded_count: u64,
+    pub(crate) consumed_count: u64,
+    /// The number of bytes kept for decoding before the buffered data, counted from the stream
+    /// offset `kept_base`, which map the buffered bytes back to their stream offsets.
+    kept_count: u64,
+    kept_base: u64,
+    /// The kept bytes after which the kept data is no longer contiguous in the stream, e.g. after
+    /// stripped whitespaces, each with its stream offset.
+    #[cfg(feature = "std")]
+    kept_gaps: Vec<(u64, u64)>,
+    /// The time spent in the engine, excluding reading and copying.
+    #[cfg(feature = "metrics")]
+    decode_elapsed: Duration,
+    /// The offsets of the blocks which could not be decoded, if they are skipped.
+    #[cfg(feature = "std")]
+    pub(crate) skipped_windows: Option<Vec<u64>>,
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Create a decoder for base64 data in the alphabet of `engine`, whose padding setting also
+    /// decides whether the `=` padding is required, accepted or rejected. By default, every byte
+    /// has to be part of the base64 data, so whitespaces are rejected, and the decoded data is
+    /// not limited.
+    #[inline]
+    pub fn new(engine: &'static base64::engine::general_purpose::GeneralPurpose) -> Decoder<N> {
+        Decoder {
+            buf: GenericArray::default(),
+            buf_length: 0,
+            buf_offset: 0,
+            temp: [0; 2],
+            temp_length: 0,
+            engine: engine.clone(),
+            ignore_whitespace: false,
+            bom_pending: true,
+            ignore_invalid: false,
+            detect_alphabet: false,
+            detected_url_safe: None,
+            strip_data_uri: false,
+            data_uri: DataUri::Done,
+            data_uri_tail: [0; 7],
+            limit: None,
+            max_inner_read: None,
+            nul_padded: false,
+            nul_reached: false,
+            stop_at_padding: false,
+            padding_reached: false,
+            trailing_length: 0,
+            pad: b'=',
+            crc32: None,
+            padded_block: None,
+            ended_cleanly: None,
+            skip_bytes: 0,
+            skip_remaining: 0,
+            decoded_count: 0,
+            consumed_count: 0,
+            kept_count: 0,
+            kept_base: 0,
+            #[cfg(feature = "std")]
+            kept_gaps: Vec::new(),
+            #[cfg(feature = "metrics")]
+            decode_elapsed: Duration::ZERO,
+            #[cfg(feature = "std")]
+            skipped_windows: None,
+        }
+    }
+}
+
+// the buffer is plain bytes, which are never pinned, but `GenericArray` is only `Unpin` for a
+// concrete length
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Unpin for Decoder<N> {}
+
+/// Only the buffered base64 data and the decoded bytes left behind are shown, not the whole
+/// buffers.
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Debug for Decoder<N> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let buf = &self.buf[self.buf_offset..(self.buf_offset + self.buf_length)];
+
+        f.debug_struct("Decoder")
+            .field("buf", &HexPreview(buf))
+            .field("buf_offset", &self.buf_offset)
+            .field("buf_length", &self.buf_length)
+            .field("temp", &HexPreview(&self.temp[..self.temp_length]))
+            .field("temp_length", &self.temp_length)
+            .field("ignore_whitespace", &self.ignore_whitespace)
+            .field("bom_pending", &self.bom_pending)
+            .field("ignore_invalid", &self.ignore_invalid)
+            .field("detect_alphabet", &self.detect_alphabet)
+            .field("detected_url_safe", &self.detected_url_safe)
+            .field("strip_data_uri", &self.strip_data_uri)
+            .field("data_uri", &self.data_uri)
+            .field("limit", &self.limit)
+            .field("max_inner_read", &self.max_inner_read)
+            .field("nul_padded", &self.nul_padded)
+            .field("nul_reached", &self.nul_reached)
+            .field("stop_at_padding", &self.stop_at_padding)
+            .field("padding_reached", &self.padding_reached)
+            .field("trailing_length", &self.trailing_length)
+            .field("pad", &self.pad)
+            .field("crc32", &self.crc32)
+            .field("padded_block", &self.padded_block)
+            .field("ended_cleanly", &self.ended_cleanly)
+            .field("skip_bytes", &self.skip_bytes)
+            .field("skip_remaining", &self.skip_remaining)
+            .field("decoded_count", &self.decoded_count)
+            .field("consumed_count", &self.consumed_count)
+            .field("kept_count", &self.kept_count)
+            .field("kept_base", &self.kept_base)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Clear all buffered data and counters.
+    pub(crate) fn reset(&mut self) {
+        if self.detected_url_safe.take().is_some() {
+            self.engine = base64::engine::general_purpose::STANDARD;
+        }
+
+        if self.strip_data_uri {
+            self.data_uri = DataUri::Scheme;
+        }
+
+        self.skip_remaining = self.skip_bytes;
+
+        if let Some((_, crc)) = self.crc32.as_mut() {
+            *crc = 0;
+        }
+
+        self.nul_reached = false;
+        self.padding_reached = false;
+        self.padded_block = None;
+        self.ended_cleanly = None;
+        self.bom_pending = true;
+
+        if let Some(skipped_windows) = self.skipped_windows.as_mut() {
+            skipped_windows.clear();
+        }
+
+        self.buf_length = 0;
+        self.buf_offset = 0;
+        self.trailing_length = 0;
+        self.temp = [0; 2];
+        self.temp_length = 0;
+        self.decoded_count = 0;
+        self.consumed_count = 0;
+        self.kept_count = 0;
+        self.kept_base = 0;
+        self.kept_gaps.clear();
+
+        #[cfg(feature = "metrics")]
+        {
+            self.decode_elapsed = Duration::ZERO;
+        }
+    }
+
+    /// Whether the base64 data maps to plain data at a fixed ratio, so that the position of a
+    /// block can be computed.
+    #[inline]
+    pub(crate) fn is_seekable(&self) -> bool {
+        !self.ignore_whitespace
+            && !self.ignore_invalid
+            && !self.strip_data_uri
+            && self.crc32.is_none()
+    }
+
+    /// Clear all buffered data to continue decoding from the block `block`, after the caller has
+    /// moved the inner reader to its start. The detected alphabet is kept.
+    pub(crate) fn seek_block(&mut self, block: u64) {
+        self.buf_length = 0;
+        self.buf_offset = 0;
+        self.temp_length = 0;
+        self.padding_reached = false;
+        self.padded_block = None;
+        self.ended_cleanly = None;
+        self.nul_reached = false;
+        self.trailing_length = 0;
+        self.skip_remaining = 0;
+        self.decoded_count = block * 3;
+        self.consumed_count = self.skip_bytes + block * 4;
+        self.kept_count = 0;
+        self.kept_base = self.consumed_count;
+        self.kept_gaps.clear();
+    }
+
+    /// Drop the first `skip_bytes` bytes of the stream before decoding.
+    #[inline]
+    pub(crate) fn set_skip_bytes(&mut self, skip_bytes: u64) {
+        self.skip_bytes = skip_bytes;
+        self.skip_remaining = skip_bytes;
+    }
+
+    /// Skip a `data:...;base64,` prefix at the start of the stream, if there is one.
+    #[inline]
+    pub(crate) fn set_strip_data_uri(&mut self) {
+        self.strip_data_uri = true;
+        self.data_uri = DataUri::Scheme;
+    }
+
+    /// The base64 data which has been read but not decoded yet, followed by the bytes read after
+    /// the padded block which ended the data.
+    #[inline]
+    pub(crate) fn leftover(&self) -> &[u8] {
+        &self.buf[self.buf_offset..(self.buf_offset + self.buf_length + self.trailing_length)]
+    }
+
+    /// Whether neither base64 data nor decoded bytes are left in the buffers.
+    #[inline]
+    pub(crate) fn is_drained(&self) -> bool {
+        self.buf_length == 0 && self.temp_length == 0
+    }
+
+    /// The number of decoded bytes in `temp`, plus 3 for every complete 4-byte block of
+    /// buffered base64 data.
+    #[inline]
+    pub(crate) fn buffered_len(&self) -> usize {
+        if self.data_uri != DataUri::Done {
+            return self.temp_length;
+        }
+
+        self.temp_length + (self.buf_length >> 2) * 3
+    }
+
+    /// Return an `InvalidByte` error for the first buffered byte which is neither a character of
+    /// the alphabet of the engine nor `=`, without decoding anything.
+    pub(crate) fn check_symbols(&self) -> Result<(), DecoderError> {
+        if self.data_uri != DataUri::Done {
+            return Ok(());
+        }
+
+        let buffered = &self.buf[self.buf_offset..(self.buf_offset + self.buf_length)];
+
+        for (i, &b) in buffered.iter().enumerate() {
+            if b != b'=' && !crate::is_symbol(&self.engine, b) {
+                return Err(DecoderError::Decode(
+                    self.decode_error(DecodeError::InvalidByte(i, b)),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Whether more base64 data is needed before a block can be decoded.
+    #[inline]
+    pub fn needs_data(&self) -> bool {
+        // decoded bytes left in `temp` can be returned without reading anything, and nothing is
+        // read after the padded block which ended the data
+        self.temp_length == 0
+            && !self.padding_reached
+            && (self.buf_length < 4 || self.data_uri != DataUri::Done)
+    }
+
+    /// The free space after the buffered base64 data, for the caller to read into.
+    #[inline]
+    pub fn spare(&mut self) -> &mut [u8] {
+        let start = self.buf_offset + self.buf_length + self.trailing_length;
+
+        let end = match self.max_inner_read {
+            Some(max_inner_read) => (start + max_inner_read).min(N::USIZE),
+            None => N::USIZE,
+        };
+
+        &mut self.buf[start..end]
+    }
+
+    /// Copy as much of `input` as fits into `spare` and accept it, for callers which are handed
+    /// chunks of base64 data instead of reading them. It returns how many bytes were consumed;
+    /// `0` means the buffer is full, so `decode` has to make room before feeding the rest.
+    #[inline]
+    pub fn feed(&mut self, input: &[u8]) -> Result<usize, DecoderError> {
+        let spare = self.spare();
+
+        let length = spare.len().min(input.len());
+
+        spare[..length].copy_from_slice(&input[..length]);
+
+        self.fill(length)?;
+
+        Ok(length)
+    }
+
+    /// Accept `length` bytes which have just been read into `spare`.
+    #[inline]
+    pub fn fill(&mut self, mut length: usize) -> Result<(), DecoderError> {
+        self.consumed_count += length as u64;
+
+        if self.padding_reached {
+            self.trailing_length += length;
+
+            return Ok(());
+        }
+
+        if self.skip_remaining > 0 {
+            length = self.skip_leading(length);
+        }
+
+        // the stream offset of the first byte which may be kept
+        let chunk_offset = self.consumed_count - length as u64;
+
+        self.mark_kept(self.buf_length, chunk_offset);
+
+        if self.nul_padded {
+            length = self.cut_nul_padding(length)?;
+        }
+
+        if self.pad != b'=' {
+            self.swap_pad(length);
+        }
+
+        if self.ignore_whitespace || self.ignore_invalid {
+            length = self.strip_ignored(length, chunk_offset);
+        }
+
+        self.buf_length += length;
+
+        if self.bom_pending && self.ignore_whitespace {
+            self.skip_bom();
+        }
+
+        if self.data_uri != DataUri::Done {
+            self.skip_data_uri()?;
+        }
+
+        if self.stop_at_padding && self.data_uri == DataUri::Done {
+            self.cut_at_padding(length);
+        }
+
+        if self.detect_alphabet {
+            self.check_alphabet(length.min(self.buf_length))?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the stream ended on a complete 4-character block, which the engine has checked
+    /// for correct padding. It is `None` until `decode_end` has returned `0`, and `Some(false)`
+    /// if the last block was shorter, e.g. an unpadded or truncated stream.
+    #[inline]
+    pub fn ended_cleanly(&self) -> Option<bool> {
+        self.ended_cleanly
+    }
+
+    /// Assert the invariants of the internal buffers, to catch a corrupted state early when
+    /// embedding the decoder: the buffered data lies within the buffer, at most 2 decoded bytes
+    /// are left behind, and the buffer is compacted before fewer than 5 bytes follow the offset.
+    #[cfg(debug_assertions)]
+    pub fn debug_invariants(&self) {
+        assert!(self.buf_offset + self.buf_length + self.trailing_length <= N::USIZE);
+        assert!(self.temp_length <= 2);
+        assert!(N::USIZE - self.buf_offset > 4);
+    }
+
+    /// Decode as much buffered data as fits in `buf`, keeping an incomplete block buffered.
+    #[inline]
+    pub fn decode(&mut self, buf: &mut [u8]) -> Result<usize, DecoderError> {
+        // the padded block ends the data as if the inner reader had reached EOF
+        if self.padding_reached && self.temp_length == 0 && self.buf_length < 4 {
+            return self.decode_end(buf);
+        }
+
+        let buf = self.limit_buf(buf)?;
+
+        let original_buf_length = buf.len();
+
+        let remaining_length = self.drain(&mut *buf).map_err(DecoderError::Decode)?.len();
+
+        let decode_length = original_buf_length - remaining_length;
+
+        self.decoded(&buf[..decode_length]);
+
+        Ok(decode_length)
+    }
+
+    /// Decode as much buffered data as fits in `buf`, after the inner reader reached EOF.
+    #[inline]
+    pub fn decode_end(&mut self, buf: &mut [u8]) -> Result<usize, DecoderError> {
+        match self.data_uri {
+            // a stream shorter than `data:` is not a data URI
+            DataUri::Scheme => self.data_uri = DataUri::Done,
+            DataUri::Header => return Err(DecoderError::DataUriNoComma),
+            DataUri::Done => (),
+        }
+
+        let requested = !buf.is_empty();
+
+        let buf = self.limit_buf(buf)?;
+
+        let original_buf_length = buf.len();
+
+        let remaining_length = self.drain_end(&mut *buf).map_err(DecoderError::Decode)?.len();
+
+        let decode_length = original_buf_length - remaining_length;
+
+        self.decoded(&buf[..decode_length]);
+
+        // the stream is over once nothing can be decoded any more
+        if requested && decode_length == 0 {
+            self.ended_cleanly.get_or_insert(true);
+
+            if let Some((expected, crc)) = self.crc32 {
+                if crc != expected {
+                    return Err(DecoderError::Crc32Mismatch(crc));
+                }
+            }
+        }
+
+        Ok(decode_length)
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Get the number of decoded bytes and the time spent decoding them, excluding I/O.
+    #[inline]
+    pub fn stats(&self) -> crate::DecodeStats {
+        crate::DecodeStats {
+            bytes: self.decoded_count,
+            elapsed: self.decode_elapsed,
+        }
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Decode data into `buf` for an asynchronous reader. `poll_read` reads base64 data from the
+    /// inner reader into the given slice. Buffered data is kept while it returns `Poll::Pending`.
+    pub(crate) fn poll_decode(
+        &mut self,
+        buf: &mut [u8],
+        mut poll_read: impl FnMut(&mut [u8]) -> Poll<Result<usize, io::Error>>,
+    ) -> Poll<Result<usize, io::Error>> {
+        while self.needs_data() {
+            match poll_read(self.spare()) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Ok(self.decode_end(buf)?)),
+                Poll::Ready(Ok(c)) => self.fill(c)?,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(Ok(self.decode(buf)?))
+    }
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Decoder<N> {
+    /// Count the data which has just been decoded.
+    #[inline]
+    fn decoded(&mut self, data: &[u8]) {
+        self.decoded_count += data.len() as u64;
+
+        if let Some((_, crc)) = self.crc32.as_mut() {
+            *crc = crate::crc32::update(*crc, data);
+        }
+    }
+
+    /// Cut the `length` newly read bytes at the first NUL after some base64 data and return how
+    /// many bytes remain. Only NULs may follow it, up to the end of the stream.
+    fn cut_nul_padding(&mut self, length: usize) -> Result<usize, DecoderError> {
+        let start = self.buf_offset + self.buf_length;
+
+        let chunk = &self.buf[start..(start + length)];
+
+        let chunk_offset = self.consumed_count - length as u64;
+
+        let nul_index = if self.nul_reached {
+            0
+        } else {
+            match chunk.iter().position(|&b| b == 0) {
+                Some(index) => index,
+                None => return Ok(length),
+            }
+        };
+
+        let has_data = self.nul_reached || self.buf_length > 0 || self.decoded_count > 0;
+
+        if nul_index == 0 && !has_data {
+            return Err(DecoderError::Decode(FromBase64Error::new(
+                chunk_offset,
+                DecodeError::InvalidByte(0, 0),
+            )));
+        }
+
+        if let Some(index) = chunk[nul_index..].iter().position(|&b| b != 0) {
+            let index = nul_index + index;
+
+            return Err(DecoderError::Decode(FromBase64Error::new(
+                chunk_offset + index as u64,
+                DecodeError::InvalidByte(index, chunk[index]),
+            )));
+        }
+
+        self.nul_reached = true;
+
+        Ok(nul_index)
+    }
+
+    /// Swap the custom padding character with `=` in the `length` newly read bytes, so that the
+    /// engine recognizes the padding and rejects a `=` in the data.
+    fn swap_pad(&mut self, length: usize) {
+        let start = self.buf_offset + self.buf_length;
+
+        for b in self.buf[start..(start + length)].iter_mut() {
+            if *b == self.pad {
+                *b = b'=';
+            } else if *b == b'=' {
+                *b = self.pad;
+            }
+        }
+    }
+
+    /// Drop the bytes still to be skipped from the `length` newly read bytes and return how many
+    /// bytes remain.
+    fn skip_leading(&mut self, length: usize) -> usize {
+        let skip_length = usize::try_from(self.skip_remaining).unwrap_or(usize::MAX).min(length);
+
+        let start = self.buf_offset + self.buf_length;
+
+        self.buf.copy_within((start + skip_length)..(start + length), start);
+
+        self.skip_remaining -= skip_length as u64;
+
+        length - skip_length
+    }
+
+    /// Remove whitespaces, or all bytes which are neither characters of the alphabet of the engine
+    /// nor `=` if `ignore_invalid` is set, from the `length` newly read bytes, which start at the
+    /// stream offset `chunk_offset`, and return how many bytes remain. A `\r` and a `\n` are
+    /// removed alike, so any mix of line endings is accepted.
+    fn strip_ignored(&mut self, length: usize, chunk_offset: u64) -> usize {
+        let start = self.buf_offset + self.buf_length;
+
+        let mut kept = 0;
+
+        let mut stripped = false;
+
+        for i in start..(start + length) {
+            let b = self.buf[i];
+
+            let ignored = if self.ignore_invalid {
+                // until the alphabet is detected, the characters of both alphabets are kept
+                let undetected = self.detect_alphabet
+                    && self.detected_url_safe.is_none()
+                    && matches!(b, b'+' | b'/' | b'-' | b'_');
+
+                b != b'=' && !undetected && !crate::is_symbol(&self.engine, b)
+            } else {
+                matches!(b, b'\r' | b'\n' | b'\t' | b' ')
+            };
+
+            if ignored {
+                stripped = true;
+            } else {
+                if stripped {
+                    stripped = false;
+
+                    self.mark_kept(self.buf_length + kept, chunk_offset + (i - start) as u64);
+                }
+
+                self.buf[start + kept] = b;
+
+                kept += 1;
+            }
+        }
+
+        kept
+    }
+
+    /// Shorten `buf` so that no more than `limit` bytes are decoded in total. Once the limit is
+    /// reached, an error is returned if there is still data to decode.
+    fn limit_buf<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut [u8], DecoderError> {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return Ok(buf),
+        };
+
+        // `seek_decoded` may have moved past the limit
+        let remaining = limit.saturating_sub(self.decoded_count);
+
+        if remaining == 0 && !buf.is_empty() && (self.temp_length > 0 || self.buf_length > 0) {
+            return Err(DecoderError::LimitExceeded(limit));
+        }
+
+        let buf_length = buf.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+
+        Ok(&mut buf[..buf_length])
+    }
+
+    /// Remove a UTF-8 BOM from the start of the buffered data, e.g. of base64 text saved by an
+    /// editor, once enough data has arrived to tell whether there is one.
+    fn skip_bom(&mut self) {
+        const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+        let length = self.buf_length.min(BOM.len());
+
+        if self.buf[self.buf_offset..(self.buf_offset + length)] != BOM[..length] {
+            self.bom_pending = false;
+        } else if length == BOM.len() {
+            self.bom_pending = false;
+
+            self.buf_left_shift(BOM.len());
+        }
+    }
+
+    /// Remove the `data:...;base64,` prefix from the buffered data as it arrives.
+    fn skip_data_uri(&mut self) -> Result<(), DecoderError> {
+        if self.data_uri == DataUri::Scheme {
+            let length = self.buf_length.min(5);
+
+            if !self.buf[self.buf_offset..(self.buf_offset + length)]
+                .eq_ignore_ascii_case(&b"data:"[..length])
+            {
+                self.data_uri = DataUri::Done;
+
+                return Ok(());
+            }
+
+            if length < 5 {
+                // a buffer too small to hold `data:` cannot tell, so take it as base64 data
+                if self.buf_offset + self.buf_length == N::USIZE {
+                    self.data_uri = DataUri::Done;
+                }
+
+                return Ok(());
+            }
+
+            self.buf_left_shift(5);
+
+            self.data_uri = DataUri::Header;
+            self.data_uri_tail = [0; 7];
+        }
+
+        let header = &self.buf[self.buf_offset..(self.buf_offset + self.buf_length)];
+
+        let (header_length, comma_found) = match header.iter().position(|&b| b == b',') {
+            Some(index) => (index, true),
+            None => (header.len(), false),
+        };
+
+        for &b in &header[..header_length] {
+            self.data_uri_tail.copy_within(1.., 0);
+            self.data_uri_tail[6] = b;
+        }
+
+        if comma_found {
+            if !self.data_uri_tail.eq_ignore_ascii_case(b";base64") {
+                return Err(DecoderError::DataUriNotBase64);
+            }
+
+            self.buf_left_shift(header_length + 1);
+
+            self.data_uri = DataUri::Done;
+        } else {
+            self.buf_left_shift(header_length);
+        }
+
+        Ok(())
+    }
+
+    /// Pick the standard or the URL-safe alphabet by the first `+`, `/`, `-` or `_` in the
+    /// last `length` buffered bytes, and reject characters of the other alphabet after that.
+    fn check_alphabet(&mut self, length: usize) -> Result<(), DecoderError> {
+        let end = self.buf_offset + self.buf_length;
+
+        for &b in &self.buf[(end - length)..end] {
+            let url_safe = match b {
+                b'+' | b'/' => false,
+                b'-' | b'_' => true,
+                _ => continue,
+            };
+
+            match self.detected_url_safe {
+                Some(detected_url_safe) => {
+                    if detected_url_safe != url_safe {
+                        return Err(DecoderError::MixedAlphabets);
+                    }
+                }
+                None => {
+                    self.detected_url_safe = Some(url_safe);
+
+                    if url_safe {
+                        self.engine = base64::engine::general_purpose::URL_SAFE;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record that the byte which is kept at the buffered index `index` comes from the stream
+    /// offset `offset`, if the kept data is not contiguous in the stream there. Only `std`
+    /// readers can strip or skip bytes, so the kept data is always contiguous otherwise.
+    #[inline]
+    fn mark_kept(&mut self, index: usize, offset: u64) {
+        #[cfg(feature = "std")]
+        {
+            if self.buffered_offset(index) == offset {
+                return;
+            }
+
+            let position = self.kept_count + index as u64;
+
+            // gaps before the one of the first buffered byte are not needed any more
+            let obsolete_length = self
+                .kept_gaps
+                .iter()
+                .skip(1)
+                .take_while(|&&(kept, _)| kept <= self.kept_count)
+                .count();
+
+            self.kept_gaps.drain(..obsolete_length);
+
+            if let Some(&(kept, _)) = self.kept_gaps.last() {
+                if kept == position {
+                    self.kept_gaps.pop();
+                }
+            }
+
+            self.kept_gaps.push((position, offset));
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = (index, offset);
+    }
+
+    /// The stream offset of the buffered byte at `index`.
+    #[inline]
+    fn buffered_offset(&self, index: usize) -> u64 {
+        let position = self.kept_count + index as u64;
+
+        #[cfg(feature = "std")]
+        {
+            if let Some(&(kept, offset)) =
+                self.kept_gaps.iter().rev().find(|&&(kept, _)| kept <= position)
+            {
+                return offset + (position - kept);
+            }
+        }
+
+        self.kept_base + position
+    }
+
+    /// Attach the stream offset and a copy of the failed block, counted from the block at
+    /// `buf_offset`, to a decode error.
+    fn decode_error(&self, error: DecodeError) -> FromBase64Error {
+        let index = match error {
+            DecodeError::InvalidByte(index, _) | DecodeError::InvalidLastSymbol(index, _) => index,
+            _ => 0,
+        };
+
+        let offset = self.buffered_offset(index);
+
+        let window_start = (index & !0b11).min(self.buf_length);
+
+        let buffered = &self.buf[self.buf_offset..(self.buf_offset + self.buf_length)];
+
+        FromBase64Error::new(offset, error).with_window(&buffered[window_start..])
+    }
+
+    /// End the data at the first padded block, once it is complete, and keep the bytes after it
+    /// apart. Only the last incomplete block and the `length` bytes just filled in are searched,
+    /// since a complete padded block would have ended the data already.
+    fn cut_at_padding(&mut self, length: usize) {
+        let start = (self.buf_length - length.min(self.buf_length)) & !0b11;
+
+        let buffered = &self.buf[(self.buf_offset + start)..(self.buf_offset + self.buf_length)];
+
+        if let Some(index) = buffered.iter().position(|&b| b == b'=') {
+            let end = ((start + index) | 0b11) + 1;
+
+            if end <= self.buf_length {
+                self.trailing_length = self.buf_length - end;
+                self.buf_length = end;
+                self.padding_reached = true;
+            }
+        }
+    }
+
+    fn buf_left_shift(&mut self, distance: usize) {
+        debug_assert!(self.buf_length >= distance);
+
+        self.buf_offset += distance;
+        self.buf_length -= distance;
+        self.kept_count += distance as u64;
+
+        if self.buf_offset >= N::USIZE - 4 {
+            let end = self.buf_offset + self.buf_length + self.trailing_length;
+
+            self.buf.copy_within(self.buf_offset..end, 0);
+
+            self.buf_offset = 0;
+        }
+    }
+
+    /// Whether blocks which cannot be decoded are skipped.
+    #[inline]
+    fn is_recovering(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.skipped_windows.is_some();
+
+        #[cfg(not(feature = "std"))]
+        false
+    }
+
+    /// Record the block at `buf_offset` as skipped if blocks which cannot be decoded are
+    /// skipped, and return whether it is.
+    #[inline]
+    fn skip_window(&mut self) -> bool {
+        #[cfg(feature = "std")]
+        {
+            let offset = self.buffered_offset(0);
+
+            if let Some(skipped_windows) = self.skipped_windows.as_mut() {
+                skipped_windows.push(offset);
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Decode the first `length` bytes of the buffered data into `output`, timing the engine if
+    /// metrics are collected.
+    #[inline]
+    fn decode_window(
+        &mut self,
+        length: usize,
+        output: &mut [u8],
+    ) -> Result<usize, base64::DecodeSliceError> {
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+
+        let result = self
+            .engine
+            .decode_slice(&self.buf[self.buf_offset..(self.buf_offset + length)], output);
+
+        #[cfg(feature = "metrics")]
+        {
+            self.decode_elapsed += start.elapsed();
+        }
+
+        result
+    }
+
+    #[inline]
+    fn drain_temp<'a>(&mut self, buf: &'a mut [u8]) -> &'a mut [u8] {
+        debug_assert!(self.temp_length > 0);
+        debug_assert!(!buf.is_empty());
+
+        let drain_length = buf.len().min(self.temp_length);
+
+        buf[..drain_length].copy_from_slice(&self.temp[..drain_length]);
+
+        self.temp.copy_within(drain_length..self.temp_length, 0);
+
+        self.temp_length -= drain_length;
+
+        &mut buf[drain_length..]
+    }
+
+    /// Remember the last of the first `length` buffered bytes, which are about to be decoded, if
+    /// it is a padded block, since only the end of the stream may follow it.
+    #[inline]
+    fn note_padded_block(&mut self, length: usize) {
+        if length >= 4 && self.buf[self.buf_offset + length - 1] == b'=' {
+            let start = self.buf_offset + length - 4;
+
+            let mut block = [0; 4];
+
+            block.copy_from_slice(&self.buf[start..(start + 4)]);
+
+            let index = block.iter().position(|&b| b == b'=').unwrap_or(3);
+
+            self.padded_block = Some((self.buffered_offset(length - 4 + index), block));
+        }
+    }
+
+    /// Reject buffered data after a decoded padded block with the same error the engine returns
+    /// for padding in the middle of the data, an `InvalidByte` for its first `=`. When blocks
+    /// which cannot be decoded are skipped, the data is decoded on instead.
+    #[inline]
+    fn check_padded_block(&mut self) -> Result<(), FromBase64Error> {
+        match self.padded_block {
+            Some(_) if self.is_recovering() => {
+                self.padded_block = None;
+
+                Ok(())
+            }
+            Some((offset, block)) if self.buf_length > 0 => {
+                let index = block.iter().position(|&b| b == b'=').unwrap_or(3);
+
+                Err(FromBase64Error::new(offset, DecodeError::InvalidByte(index, b'='))
+                    .with_window(&block))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn drain_block<'a>(
+        &mut self,
+        mut buf: &'a mut [u8],
+    ) -> Result<&'a mut [u8], FromBase64Error> {
+        debug_assert!(self.buf_length > 0);
+        debug_assert!(self.temp_length == 0);
+        debug_assert!(!buf.is_empty());
+
+        self.check_padded_block()?;
+
+        let drain_length = self.buf_length.min(4);
+
+        let block = &self.buf[self.buf_offset..(self.buf_offset + drain_length)];
+
+        // padding may only end the stream, and a window of one block cannot tell
+        let result = match block.iter().position(|&b| b == b'=') {
+            Some(index) if self.buf_length > 4 => Err(base64::DecodeSliceError::DecodeError(
+                DecodeError::InvalidByte(index, b'='),
+            )),
+            _ => Ok(()),
+        };
+
+        let mut b = [0; 3];
+
+        let decode_length = match result.and_then(|()| self.decode_window(drain_length, &mut b)) {
+            Ok(decode_length) => {
+                self.note_padded_block(drain_length);
+
+                decode_length
+            }
+            // the bad block is replaced with as many zeros as it would have been decoded to
+            Err(_) if self.skip_window() => {
+                b = [0; 3];
+
+                drain_length * 3 / 4
+            }
+            Err(err) => {
+                #[cfg(feature = "zeroize")]
+                crate::wipe(&mut b);
+
+                return Err(self.decode_error(super::to_decode_error(err)));
+            }
+        };
+
+        self.buf_left_shift(drain_length);
+
+        let buf_length = buf.len();
+
+        if buf_length >= decode_length {
+            buf[..decode_length].copy_from_slice(&b[..decode_length]);
+
+            buf = &mut buf[decode_length..];
+        } else {
+            buf.copy_from_slice(&b[..buf_length]);
+
+            buf = &mut buf[buf_length..];
+
+            self.temp_length = decode_length - buf_length;
+
+            self.temp[..self.temp_length].copy_from_slice(&b[buf_length..decode_length]);
+        }
+
+        #[cfg(feature = "zeroize")]
+        crate::wipe(&mut b);
+
+        Ok(buf)
+    }
+
+    fn drain<'a>(&mut self, mut buf: &'a mut [u8]) -> Result<&'a mut [u8], FromBase64Error> {
+        if buf.is_empty() {
+            return Ok(buf);
+        }
+
+        if self.temp_length > 0 {
+            buf = self.drain_temp(buf);
+
+            // data after a padded block is rejected by the next call, after its last bytes
+            if self.buf_length < 4 || self.padded_block.is_some() {
+                return Ok(buf);
+            }
+        }
+
+        self.check_padded_block()?;
+
+        // no more than a full buffer of base64 data can be decoded at once, and clamping keeps
+        // the size math below from overflowing for huge buffers on 32-bit targets
+        let buf_length = buf.len().min(N::USIZE);
+
+        if buf_length >= 3 {
+            debug_assert!(self.temp_length == 0);
+
+            let actual_max_read_size = (buf_length / 3) << 2; // (buf_length / 3) * 4
+            let max_available_self_buf_length = self.buf_length & !0b11;
+
+            let drain_length = max_available_self_buf_length.min(actual_max_read_size);
+
+            match self.decode_window(drain_length, &mut *buf) {
+                Ok(decode_length) => {
+                    buf = &mut buf[decode_length..];
+
+                    self.note_padded_block(drain_length);
+                    self.buf_left_shift(drain_length);
+                }
+                // the bad block is looked for block by block below, also to tell the offset of a
+                // padding error, which the engine reports without a position
+                Err(_) if self.is_recovering() => (),
+                Err(base64::DecodeSliceError::DecodeError(
+                    DecodeError::InvalidPadding | DecodeError::InvalidLength,
+                )) => (),
+                Err(err) => return Err(self.decode_error(super::to_decode_error(err))),
+            }
+        }
+
+        while !buf.is_empty() && self.buf_length >= 4 {
+            buf = self.drain_block(buf)?;
+        }
+
+        Ok(buf)
+    }
+
+    #[inline]
+    fn drain_end<'a>(
+        &mut self,
+        mut buf: &'a mut [u8],
+    ) -> Result<&'a mut [u8], FromBase64Error> {
+        if buf.is_empty() {
+            return Ok(buf);
+        }
+
+        if self.temp_length > 0 {
+            buf = self.drain_temp(buf);
+        }
+
+        if !buf.is_empty() && self.buf_length > 0 {
+            let truncated = self.buf_length < 4;
+
+            let buf = self.drain_block(buf)?;
+
+            // only once the last block has been decoded, since the stream has not ended otherwise
+            if truncated {
+                self.ended_cleanly = Some(false);
+            }
+
+            Ok(buf)
+        } else {
+            Ok(buf)
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Drop for Decoder<N> {
+    #[inline]
+    fn drop(&mut self) {
+        crate::wipe(&mut self.buf);
+        crate::wipe(&mut self.temp);
+    }
+}
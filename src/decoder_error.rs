@@ -0,0 +1,73 @@
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::io::{self, ErrorKind};
+
+use crate::FromBase64Error;
+
+/// An error which occurs when `Decoder` cannot decode a base64 stream.
+///
+/// The readers convert it into an `io::Error`, in which a `FromBase64Error` is kept as the inner
+/// error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecoderError {
+    /// The base64 data is invalid.
+    Decode(FromBase64Error),
+    /// The decoded data exceeds the limit, in bytes.
+    LimitExceeded(u64),
+    /// Both standard and URL-safe base64 characters appear in the stream.
+    MixedAlphabets,
+    /// The data URI has no comma before its data.
+    DataUriNoComma,
+    /// The data URI is not base64 encoded.
+    DataUriNotBase64,
+    /// The CRC-32 checksum of the decoded data does not match the expected one. It holds the
+    /// actual checksum.
+    Crc32Mismatch(u32),
+}
+
+impl Display for DecoderError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            DecoderError::Decode(err) => Display::fmt(err, f),
+            DecoderError::LimitExceeded(limit) => {
+                write!(f, "the decoded data exceeds the limit of {} bytes", limit)
+            }
+            DecoderError::MixedAlphabets => {
+                f.write_str("both standard and URL-safe base64 characters appear in the stream")
+            }
+            DecoderError::DataUriNoComma => {
+                f.write_str("the data URI has no comma before its data")
+            }
+            DecoderError::DataUriNotBase64 => f.write_str("the data URI is not base64 encoded"),
+            DecoderError::Crc32Mismatch(crc) => {
+                write!(f, "the CRC-32 checksum {:08x} of the decoded data does not match", crc)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for DecoderError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecoderError::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<DecoderError> for io::Error {
+    #[inline]
+    fn from(err: DecoderError) -> io::Error {
+        match err {
+            DecoderError::Decode(err) => io::Error::other(err),
+            DecoderError::LimitExceeded(_) => io::Error::other(err),
+            _ => io::Error::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
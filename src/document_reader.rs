@@ -0,0 +1,152 @@
+use std::io::{self, ErrorKind, Read};
+
+/// Read data up to a separator, for decoding several base64 documents in one stream, e.g. PEM
+/// files with multiple objects. It is created by `FromBase64Reader::new_documents`.
+///
+/// Reading returns `0` at the separator, until `next_document` is called.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct DocumentReader<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    separator: &'static [u8],
+    held: Vec<u8>,
+    held_offset: usize,
+    at_separator: bool,
+    at_end: bool,
+}
+
+impl<R: Read> DocumentReader<R> {
+    /// # Panics
+    ///
+    /// Panics if `separator` is empty.
+    #[inline]
+    pub(crate) fn new(reader: R, separator: &'static [u8]) -> DocumentReader<R> {
+        assert!(!separator.is_empty(), "the separator must not be empty");
+
+        DocumentReader {
+            inner: reader,
+            separator,
+            held: Vec::new(),
+            held_offset: 0,
+            at_separator: false,
+            at_end: false,
+        }
+    }
+
+    /// Get a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Whether the current document has ended at a separator rather than at the end of the
+    /// stream.
+    #[inline]
+    pub fn is_at_separator(&self) -> bool {
+        self.at_separator
+    }
+
+    /// Skip the rest of the current document and the separator after it. `false` is returned if
+    /// there is no document left.
+    pub fn next_document(&mut self) -> Result<bool, io::Error> {
+        let mut discarded = [0u8; 256];
+
+        while self.read(&mut discarded)? > 0 {}
+
+        if !self.at_separator {
+            return Ok(false);
+        }
+
+        self.at_separator = false;
+
+        // a separator at the end of the stream is not followed by another document
+        if self.held_offset == self.held.len() && !self.at_end && !self.read_more()? {
+            self.at_end = true;
+        }
+
+        Ok(self.held_offset < self.held.len())
+    }
+
+    /// Read more data from the inner reader and append it to `held`. `false` is returned at EOF.
+    fn read_more(&mut self) -> Result<bool, io::Error> {
+        if self.held_offset > 0 {
+            self.held.drain(..self.held_offset);
+
+            self.held_offset = 0;
+        }
+
+        let held_length = self.held.len();
+
+        self.held.resize(held_length + self.separator.len().max(4096), 0);
+
+        loop {
+            match self.inner.read(&mut self.held[held_length..]) {
+                Ok(c) => {
+                    self.held.truncate(held_length + c);
+
+                    return Ok(c > 0);
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.held.truncate(held_length);
+
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for DocumentReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if self.at_separator || buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let held = &self.held[self.held_offset..];
+
+            let available_length = match held
+                .windows(self.separator.len())
+                .position(|window| window == self.separator)
+            {
+                Some(0) => {
+                    self.held_offset += self.separator.len();
+                    self.at_separator = true;
+
+                    return Ok(0);
+                }
+                Some(index) => index,
+                None if self.at_end => held.len(),
+                // a tail which may be the start of the separator is held back
+                None => {
+                    let partial_length = (1..self.separator.len().min(held.len() + 1))
+                        .rev()
+                        .find(|&length| held.ends_with(&self.separator[..length]))
+                        .unwrap_or(0);
+
+                    held.len() - partial_length
+                }
+            };
+
+            if available_length > 0 {
+                let drain_length = available_length.min(buf.len());
+
+                buf[..drain_length].copy_from_slice(&held[..drain_length]);
+
+                self.held_offset += drain_length;
+
+                return Ok(drain_length);
+            }
+
+            if self.at_end {
+                return Ok(0);
+            }
+
+            if !self.read_more()? {
+                self.at_end = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::io::ErrorKind;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::task::Poll;
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4};
+use generic_array::{ArrayLength, GenericArray};
+
+use base64::{self,
+    Engine,
+};
+
+use crate::HexPreview;
+
+/// The encoding state shared by the blocking and the asynchronous base64 writers. It does not
+/// own the inner writer; callers write out `pending` and report it with `consume`.
+pub(crate) struct Encoder<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> {
+    buf: [u8; 3],
+    buf_length: usize,
+    temp: GenericArray<u8, N>,
+    temp_offset: usize,
+    temp_length: usize,
+    engine: &'static base64::engine::general_purpose::GeneralPurpose,
+}
+
+// the buffer is plain bytes, which are never pinned, but `GenericArray` is only `Unpin` for a
+// concrete length
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Unpin for Encoder<N> {}
+
+/// Only the plain bytes of the incomplete block and the pending base64 data are shown, not the
+/// whole buffers.
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Debug for Encoder<N> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Encoder")
+            .field("buf", &HexPreview(&self.buf[..self.buf_length]))
+            .field("buf_length", &self.buf_length)
+            .field("temp", &HexPreview(self.pending()))
+            .field("temp_offset", &self.temp_offset)
+            .field("temp_length", &self.temp_length)
+            .finish()
+    }
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Encoder<N> {
+    #[inline]
+    pub(crate) fn new(engine: &'static base64::engine::general_purpose::GeneralPurpose) -> Encoder<N> {
+        Encoder {
+            buf: [0; 3],
+            buf_length: 0,
+            temp: GenericArray::default(),
+            temp_offset: 0,
+            temp_length: 0,
+            engine,
+        }
+    }
+
+    /// The base64 data which has been encoded but not written out yet.
+    #[inline]
+    pub(crate) fn pending(&self) -> &[u8] {
+        &self.temp[self.temp_offset..self.temp_length]
+    }
+
+    /// Mark `length` bytes of `pending` as written out.
+    #[inline]
+    pub(crate) fn consume(&mut self, length: usize) {
+        debug_assert!(self.temp_offset + length <= self.temp_length);
+
+        self.temp_offset += length;
+
+        if self.temp_offset == self.temp_length {
+            self.temp_offset = 0;
+            self.temp_length = 0;
+        }
+    }
+
+    /// Take plain data from `buf` and return how many bytes have been taken. The `pending` data
+    /// must have been written out before calling this.
+    pub(crate) fn encode(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        debug_assert!(self.temp_length == 0);
+
+        if self.buf_length > 0 {
+            debug_assert!(self.buf_length < 3);
+
+            let drain_length = (3 - self.buf_length).min(buf.len());
+
+            self.buf[self.buf_length..(self.buf_length + drain_length)]
+                .copy_from_slice(&buf[..drain_length]);
+
+            self.buf_length += drain_length;
+
+            if self.buf_length == 3 {
+                self.drain_block()?;
+            }
+
+            Ok(drain_length)
+        } else if buf.len() >= 3 {
+            let max_available_buf_length =
+                (buf.len() - (buf.len() % 3)).min((N::USIZE >> 2) * 3); // (N::USIZE / 4) * 3
+
+            self.temp_length = self.engine.encode_slice(
+                buf[..max_available_buf_length].as_ref(),
+                &mut self.temp,
+            ).map_err(super::to_io_error)?;
+
+            Ok(max_available_buf_length)
+        } else {
+            let buf_length = buf.len();
+
+            self.buf[..buf_length].copy_from_slice(buf);
+
+            self.buf_length = buf_length;
+
+            Ok(buf_length)
+        }
+    }
+
+    /// Encode the last incomplete block, with padding if the engine pads. The `pending` data
+    /// must have been written out before calling this.
+    pub(crate) fn finish(&mut self) -> Result<(), io::Error> {
+        debug_assert!(self.temp_length == 0);
+
+        if self.buf_length > 0 {
+            self.drain_block()?;
+        }
+
+        Ok(())
+    }
+
+    fn drain_block(&mut self) -> Result<(), io::Error> {
+        debug_assert!(self.buf_length > 0);
+
+        self.temp_length = self.engine.encode_slice(
+            self.buf[..self.buf_length].as_ref(),
+            &mut self.temp,
+        ).map_err(super::to_io_error)?;
+
+        self.buf_length = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Encoder<N> {
+    /// Write `pending` out for an asynchronous writer. `poll_write` writes the given slice to the
+    /// inner writer. Data written partially is resumed instead of being encoded again.
+    pub(crate) fn poll_write_pending(
+        &mut self,
+        mut poll_write: impl FnMut(&[u8]) -> Poll<Result<usize, io::Error>>,
+    ) -> Poll<Result<(), io::Error>> {
+        loop {
+            let pending = self.pending();
+
+            if pending.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            match poll_write(pending) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(c)) => self.consume(c),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Take plain data from `buf` for an asynchronous writer and return how many bytes have been
+    /// taken.
+    pub(crate) fn poll_encode(
+        &mut self,
+        buf: &[u8],
+        mut poll_write: impl FnMut(&[u8]) -> Poll<Result<usize, io::Error>>,
+    ) -> Poll<Result<usize, io::Error>> {
+        // the encoded data of earlier calls is written out first, so it is never encoded twice
+        match self.poll_write_pending(&mut poll_write) {
+            Poll::Ready(Ok(())) => (),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        let c = self.encode(buf)?;
+
+        // the taken data is buffered, so the inner writer being not ready yet is fine here
+        if let Poll::Ready(Err(e)) = self.poll_write_pending(poll_write) {
+            return Poll::Ready(Err(e));
+        }
+
+        Poll::Ready(Ok(c))
+    }
+
+    /// Encode the last incomplete block and write all encoded data out for an asynchronous
+    /// writer.
+    pub(crate) fn poll_finish(
+        &mut self,
+        mut poll_write: impl FnMut(&[u8]) -> Poll<Result<usize, io::Error>>,
+    ) -> Poll<Result<(), io::Error>> {
+        match self.poll_write_pending(&mut poll_write) {
+            Poll::Ready(Ok(())) => (),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        self.finish()?;
+
+        self.poll_write_pending(poll_write)
+    }
+}
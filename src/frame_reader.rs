@@ -0,0 +1,91 @@
+use std::io::{self, ErrorKind, Read};
+
+/// Read a fixed number of bytes at a time, for decoding base64 frames of a larger protocol, e.g.
+/// length-prefixed ones. It is created by `FromBase64Reader::new_framed`.
+///
+/// Reading returns `0` at the end of each frame, until `next_frame` is called. Nothing beyond the
+/// frame is read from the inner reader, so it is positioned right after the frame once the frame
+/// has been read through.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct FrameReader<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    frame_len: u64,
+    remaining: u64,
+}
+
+impl<R: Read> FrameReader<R> {
+    #[inline]
+    pub(crate) fn new(reader: R, frame_len: u64) -> FrameReader<R> {
+        FrameReader {
+            inner: reader,
+            frame_len,
+            remaining: frame_len,
+        }
+    }
+
+    /// Get a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader, e.g. to read the header of the next frame
+    /// once the current one has been read through.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Get the number of bytes of the current frame which have not been read yet.
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Set the length of the frames started by the following calls to `next_frame`.
+    #[inline]
+    pub fn set_frame_len(&mut self, frame_len: u64) {
+        self.frame_len = frame_len;
+    }
+
+    /// Skip the rest of the current frame and start the next one.
+    pub fn next_frame(&mut self) -> Result<(), io::Error> {
+        let mut discarded = [0u8; 256];
+
+        while self.read(&mut discarded)? > 0 {}
+
+        self.remaining = self.frame_len;
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for FrameReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        let max_length = buf.len().min(self.remaining.try_into().unwrap_or(usize::MAX));
+
+        if max_length == 0 {
+            return Ok(0);
+        }
+
+        loop {
+            match self.inner.read(&mut buf[..max_length]) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "the stream ended in the middle of a frame",
+                    ));
+                }
+                Ok(c) => {
+                    self.remaining -= c as u64;
+
+                    return Ok(c);
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use base64::DecodeError;
+
+/// An error which occurs when `FromBase64Reader` meets base64 data it cannot decode.
+///
+/// It is wrapped in the `io::Error` returned by the reader, and can be retrieved with
+/// `io::Error::get_ref` and `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromBase64Error {
+    offset: u64,
+    error: DecodeError,
+    /// A copy of the block which cannot be decoded, taken before the buffer is reused.
+    window: [u8; 4],
+    window_length: u8,
+}
+
+impl FromBase64Error {
+    #[inline]
+    pub(crate) fn new(offset: u64, error: DecodeError) -> FromBase64Error {
+        FromBase64Error {
+            offset,
+            error,
+            window: [0; 4],
+            window_length: 0,
+        }
+    }
+
+    /// Attach the first 4 bytes of `data`, the block which cannot be decoded.
+    #[inline]
+    pub(crate) fn with_window(mut self, data: &[u8]) -> FromBase64Error {
+        let window_length = data.len().min(4);
+
+        self.window[..window_length].copy_from_slice(&data[..window_length]);
+        self.window_length = window_length as u8;
+
+        self
+    }
+
+    /// Get the offset in the base64 stream of the invalid byte, or of the start of the block
+    /// which cannot be decoded.
+    ///
+    /// The offset is exact in the original input: it counts every byte read from the inner
+    /// reader before the failed one, including skipped bytes, stripped whitespaces and other
+    /// ignored bytes, but none after it.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Get the underlying decode error. Its offsets are relative to the decoded chunk, not to
+    /// the stream.
+    #[inline]
+    pub fn error(&self) -> &DecodeError {
+        &self.error
+    }
+
+    /// Get the block of up to 4 base64 bytes which cannot be decoded, e.g. `b"A!B="`, for logging
+    /// a corrupted stream. It is empty if the error is not about a block, e.g. a misplaced NUL.
+    #[inline]
+    pub fn window(&self) -> &[u8] {
+        &self.window[..self.window_length as usize]
+    }
+}
+
+impl Display for FromBase64Error {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "invalid base64 data at byte {}", self.offset)?;
+
+        if self.window_length > 0 {
+            write!(f, " in window \"{}\"", self.window().escape_ascii())?;
+        }
+
+        write!(f, ": {}", self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for FromBase64Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -1,37 +1,411 @@
-use std::intrinsics::{copy, copy_nonoverlapping};
-use std::io::{self, ErrorKind, Read};
+#[cfg(feature = "read_buf")]
+use std::{io::BorrowedCursor, mem::MaybeUninit, slice};
+use std::io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::str;
 
+#[cfg(feature = "bytes")]
+use bytes::BytesMut;
 use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
-use generic_array::{ArrayLength, GenericArray};
+use generic_array::ArrayLength;
 
-use base64::{self,
-    Engine,
-    DecodeError,
-};
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+
+use crate::decoded_buffer::DecodedBuffer;
+use crate::decoder::Decoder;
+use crate::{DecoderError, DocumentReader, FrameReader, QuotedPrintableReader, ReadDecodedError};
+
+/// The standard alphabet which accepts base64 data with or without padding.
+pub(crate) const STANDARD_INDIFFERENT_PAD: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// The URL-safe alphabet which accepts base64 data with or without padding.
+pub(crate) const URL_SAFE_INDIFFERENT_PAD: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// The crypt(3) alphabet, `./0-9A-Za-z`, without padding.
+pub(crate) const CRYPT_NO_PAD: GeneralPurpose =
+    GeneralPurpose::new(&base64::alphabet::CRYPT, base64::engine::general_purpose::NO_PAD);
+
+/// The bcrypt alphabet, `./A-Za-z0-9`, without padding.
+pub(crate) const BCRYPT_NO_PAD: GeneralPurpose =
+    GeneralPurpose::new(&base64::alphabet::BCRYPT, base64::engine::general_purpose::NO_PAD);
 
 /// Read base64 data and decode them to plain data.
+///
+/// The whole inner reader is one base64 string, however its reads are chunked: a block may be
+/// split between reads at any byte, and it is completed by the following ones, so the chunks must
+/// simply be read in order. Padding is only accepted at the end of that string, not at the end of
+/// each chunk.
+///
+/// It can be cloned if the inner reader can, and the clone resumes decoding from the same
+/// position.
+///
+/// The base64 buffer of `N` bytes is stored inline, not allocated. To decode many short streams
+/// in a row, keep one reader and hand it each stream with `reset`, so its buffers are reused, or
+/// pick a smaller `N` with `new2`. To move readers around or store many of them, box them with
+/// `new_boxed`.
 #[derive(Educe)]
-#[educe(Debug)]
+#[educe(Debug, Clone)]
 pub struct FromBase64Reader<
     R: Read,
     N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
 > {
     #[educe(Debug(ignore))]
     inner: R,
-    buf: GenericArray<u8, N>,
-    buf_length: usize,
-    buf_offset: usize,
-    temp: [u8; 2],
-    temp_length: usize,
-    #[educe(Debug(ignore))]
-    engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    pub(crate) decoder: Decoder<N>,
+    decoded: DecodedBuffer,
 }
 
 impl<R: Read> FromBase64Reader<R> {
+    /// Create a reader which decodes standard base64 data. A stream which ends with a truncated
+    /// block, i.e. 1 character or 2 or 3 characters without `=` padding, is rejected with an
+    /// error instead of being decoded short.
+    ///
+    /// Like every reader of this crate, it only accepts canonical base64 data (RFC 4648 §3.5): a
+    /// last character whose unused low bits are not zero, e.g. `9` in `SGVsbG9=`, is rejected
+    /// with `DecodeError::InvalidLastSymbol`, so each plain data has exactly one encoding.
     #[inline]
     pub fn new(reader: R) -> FromBase64Reader<R> {
         Self::new2(reader, &base64::engine::general_purpose::STANDARD)
     }
+
+    /// Create a reader like `new`, on the heap. The box is pointer-sized, so it is cheap to
+    /// move around or to keep many readers in a collection, while `new` avoids the allocation
+    /// for a reader which is used once in place. Use `Box::new` with `new2` for another buffer
+    /// size.
+    #[inline]
+    pub fn new_boxed(reader: R) -> Box<FromBase64Reader<R>> {
+        Box::new(Self::new(reader))
+    }
+
+    /// Create a reader like `new`, but read the first buffer of base64 data right away and
+    /// return a `FromBase64Error` if it contains a byte which is not a standard base64 character,
+    /// so that a stream can be sniffed cheaply before it is routed to a decoder. The data read is
+    /// kept for decoding. Later invalid bytes are still reported by `read`.
+    #[inline]
+    pub fn try_new(reader: R) -> Result<FromBase64Reader<R>, io::Error> {
+        let mut reader = Self::new(reader);
+
+        reader.check_first_buffer()?;
+
+        Ok(reader)
+    }
+
+    /// Create a reader which decodes with the given engine, e.g. `URL_SAFE` for base64url data.
+    ///
+    /// A custom alphabet or padding mode can be used through a `const` engine built with
+    /// `GeneralPurpose::new`.
+    #[inline]
+    pub fn new_with_engine(
+        reader: R,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> FromBase64Reader<R> {
+        Self::new2(reader, engine)
+    }
+
+    /// Create a reader which skips `\r`, `\n`, `\t` and space bytes, e.g. for PEM or MIME bodies.
+    /// A UTF-8 BOM at the start of the stream, as saved by some editors, is skipped too.
+    #[inline]
+    pub fn new_ignoring_whitespace(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.ignore_whitespace = true;
+
+        reader
+    }
+
+    /// Create a reader which skips every byte that is neither a character of the standard
+    /// alphabet nor `=`, e.g. for messy input with stray characters. Unlike
+    /// `new_ignoring_whitespace`, which rejects such bytes, it cannot catch corrupted data.
+    #[inline]
+    pub fn new_lenient(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.ignore_invalid = true;
+
+        reader
+    }
+
+    /// Create a reader which decodes standard or URL-safe base64 data, picking the alphabet by
+    /// the first `+`, `/`, `-` or `_` character. An error is returned if characters of both
+    /// alphabets appear.
+    #[inline]
+    pub fn new_auto(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.detect_alphabet = true;
+
+        reader
+    }
+
+    /// Create a reader which skips a `data:[<media type>][;<parameter>];base64,` prefix, e.g. for
+    /// data URIs supplied by browsers. Data without the prefix is decoded from the start. An error
+    /// is returned if the data URI is not base64 encoded.
+    #[inline]
+    pub fn new_data_uri(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.set_strip_data_uri();
+
+        reader
+    }
+
+    /// Create a reader which drops the first `skip_bytes` bytes of the inner reader, e.g. a binary
+    /// header before the base64 payload. They are skipped as they are read, so the header may
+    /// arrive over several reads.
+    #[inline]
+    pub fn new_skipping(reader: R, skip_bytes: u64) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.set_skip_bytes(skip_bytes);
+
+        reader
+    }
+
+    /// Create a reader which decodes at most `max_bytes` bytes, e.g. for untrusted input. The data
+    /// up to the limit is returned, then the next read returns an error if the stream goes on.
+    #[inline]
+    pub fn with_limit(reader: R, max_bytes: u64) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.limit = Some(max_bytes);
+
+        reader
+    }
+
+    /// Create a reader which decodes standard base64 data padded with `pad` instead of `=`, e.g.
+    /// `.` as used by some legacy systems. `=` is not accepted as padding then.
+    ///
+    /// An `InvalidInput` error is returned if `pad` is a character of the base64 alphabet.
+    #[inline]
+    pub fn new_with_padding(reader: R, pad: u8) -> Result<FromBase64Reader<R>, io::Error> {
+        if base64::alphabet::STANDARD.as_str().as_bytes().contains(&pad) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "the padding character is a base64 character",
+            ));
+        }
+
+        let mut reader = Self::new(reader);
+
+        reader.decoder.pad = pad;
+
+        Ok(reader)
+    }
+
+    /// Create a reader which decodes with a 256-entry decode table, for base64-like encodings
+    /// which have no alphabet in the `base64` crate. `decode_table[b]` is the 6-bit value of the
+    /// byte `b`, and all the other bytes map to one invalid value of at least 64. Trailing `=`
+    /// padding is optional.
+    ///
+    /// An `InvalidInput` error is returned if the values `0` to `63` are not each taken by exactly
+    /// one byte, if the other bytes map to more than one invalid value, or if a character is not
+    /// printable ASCII or is `=`, which the engine cannot decode.
+    pub fn new_with_tables(
+        reader: R,
+        decode_table: &[u8; 256],
+    ) -> Result<FromBase64Reader<R>, io::Error> {
+        let invalid_table = |message| io::Error::new(ErrorKind::InvalidInput, message);
+
+        let mut symbols = [0u8; 64];
+        let mut seen_values = 0u64;
+        let mut invalid_value = None;
+
+        for (b, &value) in decode_table.iter().enumerate() {
+            if value < 64 {
+                if seen_values & (1 << value) != 0 {
+                    return Err(invalid_table("the decode table maps two bytes to the same value"));
+                }
+
+                seen_values |= 1 << value;
+                symbols[value as usize] = b as u8;
+            } else if *invalid_value.get_or_insert(value) != value {
+                return Err(invalid_table("the decode table has more than one invalid value"));
+            }
+        }
+
+        if seen_values != u64::MAX {
+            return Err(invalid_table("the decode table does not map a byte to every value"));
+        }
+
+        let alphabet = str::from_utf8(&symbols)
+            .ok()
+            .and_then(|symbols| base64::alphabet::Alphabet::new(symbols).ok())
+            .ok_or_else(|| {
+                invalid_table("the characters of the decode table must be printable ASCII but `=`")
+            })?;
+
+        let mut reader = Self::new(reader);
+
+        reader.decoder.engine = GeneralPurpose::new(
+            &alphabet,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+
+        Ok(reader)
+    }
+
+    /// Create a reader which checks the decoded data against the CRC-32 (IEEE) checksum
+    /// `expected`. The read which reaches the end of the stream returns an error instead of `0`
+    /// if the checksum does not match.
+    #[inline]
+    pub fn with_crc32(reader: R, expected: u32) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.crc32 = Some((expected, 0));
+
+        reader
+    }
+
+    /// Create a reader for base64 data padded with trailing NULs, e.g. a field of a fixed-width
+    /// record. The first NUL after some base64 data ends the data, and only NULs may follow it
+    /// up to the end of the inner reader, which can be limited to the field with `Read::take`.
+    /// A NUL before any base64 data is an error.
+    #[inline]
+    pub fn new_nul_padded(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.nul_padded = true;
+
+        reader
+    }
+
+    /// Create a reader which ends the base64 data at the first padded block, e.g. when a
+    /// producer appends a checksum or other bytes after the padding. The padded block is decoded
+    /// as the last one and the reader returns EOF after it, instead of rejecting what follows.
+    ///
+    /// The bytes after the padding which have already been read from the inner reader are not
+    /// lost: they are returned by `buffered_input` and `into_inner`, followed by the rest of the
+    /// inner reader. Data without padding is decoded up to the end of the inner reader as usual.
+    #[inline]
+    pub fn new_stop_at_padding(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.stop_at_padding = true;
+
+        reader
+    }
+
+    /// Create a reader which recovers what it can from corrupted base64 data, e.g. a damaged
+    /// archive. **Decoding is lossy then**: every 4-byte block which cannot be decoded is skipped
+    /// and replaced with zero bytes, 3 for a whole block, without any error. The offsets of the
+    /// skipped blocks can be got from `skipped_windows`, which should be checked, since the
+    /// decoded data cannot be trusted otherwise.
+    ///
+    /// Never use it for data which is supposed to be intact.
+    #[inline]
+    pub fn new_recovering(reader: R) -> FromBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.decoder.skipped_windows = Some(Vec::new());
+
+        reader
+    }
+
+    /// Create a reader which also accepts base64 data whose trailing `=` padding is omitted.
+    #[inline]
+    pub fn new_no_pad(reader: R) -> FromBase64Reader<R> {
+        Self::new2(reader, &STANDARD_INDIFFERENT_PAD)
+    }
+
+    /// Create a reader which decodes unpadded base64 data in the crypt(3) alphabet, `./0-9A-Za-z`,
+    /// e.g. the salts and hashes of password hash strings. Its characters map to other values than
+    /// in the standard alphabet, rather than just replacing `+` and `/`.
+    #[inline]
+    pub fn new_crypt(reader: R) -> FromBase64Reader<R> {
+        Self::new2(reader, &CRYPT_NO_PAD)
+    }
+
+    /// Create a reader which decodes unpadded base64 data in the bcrypt alphabet, `./A-Za-z0-9`,
+    /// which orders the letters before the digits, unlike the crypt(3) alphabet of `new_crypt`.
+    #[inline]
+    pub fn new_bcrypt(reader: R) -> FromBase64Reader<R> {
+        Self::new2(reader, &BCRYPT_NO_PAD)
+    }
+}
+
+impl<R: Read> FromBase64Reader<DocumentReader<R>> {
+    /// Create a reader which decodes several base64 documents separated by `separator`, e.g.
+    /// `b"-----"`. Reading returns `0` at the end of each document; call `next_document` to go on
+    /// with the next one. Whitespaces are ignored, so the separator can sit on a line of its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is empty.
+    #[inline]
+    pub fn new_documents(
+        reader: R,
+        separator: &'static [u8],
+    ) -> FromBase64Reader<DocumentReader<R>> {
+        Self::new_ignoring_whitespace(DocumentReader::new(reader, separator))
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>
+    FromBase64Reader<DocumentReader<R>, N>
+{
+    /// Skip the rest of the current document and start decoding the next one. The counters and
+    /// buffers are cleared, as by `reset`. `false` is returned if there is no document left.
+    ///
+    /// A last document with no base64 data, e.g. the line ending after the last separator, is
+    /// not counted, so the next document is decoded ahead by `peek`, and its errors may be
+    /// returned here.
+    pub fn next_document(&mut self) -> Result<bool, io::Error> {
+        if !self.inner.next_document()? {
+            return Ok(false);
+        }
+
+        self.decoder.reset();
+        self.decoded.clear();
+
+        Ok(!self.peek(1)?.is_empty() || self.inner.is_at_separator())
+    }
+}
+
+impl<R: Read> FromBase64Reader<QuotedPrintableReader<R>> {
+    /// Create a reader which decodes base64 data wrapped in quoted-printable, e.g. an email body
+    /// encoded twice. The soft line breaks and the escapes, such as `=3D` for the padding, are
+    /// undone by a `QuotedPrintableReader`, and the hard line breaks are skipped as whitespaces.
+    #[inline]
+    pub fn new_quoted_printable(reader: R) -> FromBase64Reader<QuotedPrintableReader<R>> {
+        let mut reader = Self::new(QuotedPrintableReader::new(reader));
+
+        reader.decoder.ignore_whitespace = true;
+
+        reader
+    }
+}
+
+impl<R: Read> FromBase64Reader<FrameReader<R>> {
+    /// Create a reader which decodes a frame of exactly `frame_len` base64 bytes, e.g. the payload
+    /// of a length-prefixed frame. Reading returns `0` at the end of the frame; call `next_frame`
+    /// to go on with the next one. No base64 data beyond the frame is read from `reader`.
+    #[inline]
+    pub fn new_framed(reader: R, frame_len: u64) -> FromBase64Reader<FrameReader<R>> {
+        Self::new(FrameReader::new(reader, frame_len))
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>
+    FromBase64Reader<FrameReader<R>, N>
+{
+    /// Skip the rest of the current frame and start decoding the next one, whose length can be
+    /// changed beforehand with `get_mut().set_frame_len`. The counters and buffers are cleared,
+    /// as by `reset`.
+    pub fn next_frame(&mut self) -> Result<(), io::Error> {
+        self.inner.next_frame()?;
+
+        self.decoder.reset();
+        self.decoded.clear();
+
+        Ok(())
+    }
 }
 
 impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Reader<R, N> {
@@ -39,186 +413,689 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase
     pub fn new2(reader: R, engine: &'static base64::engine::general_purpose::GeneralPurpose) -> FromBase64Reader<R, N> {
         FromBase64Reader {
             inner: reader,
-            buf: GenericArray::default(),
-            buf_length: 0,
-            buf_offset: 0,
-            temp: [0; 2],
-            temp_length: 0,
-            engine,
+            decoder: Decoder::new(engine),
+            decoded: DecodedBuffer::default(),
         }
     }
 }
 
 impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Reader<R, N> {
-    fn buf_left_shift(&mut self, distance: usize) {
-        debug_assert!(self.buf_length >= distance);
+    /// Get a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
 
-        self.buf_offset += distance;
+    /// Get a mutable reference to the inner reader.
+    ///
+    /// Reading data directly from the inner reader is not recommended, because the bytes which
+    /// have been buffered by this reader will no longer line up with the rest of the stream.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
 
-        if self.buf_offset >= N::USIZE - 4 {
-            unsafe {
-                copy(
-                    self.buf.as_ptr().add(self.buf_offset),
-                    self.buf.as_mut_ptr(),
-                    self.buf_length,
-                );
-            }
+    /// Get the number of plain bytes which have been decoded so far.
+    #[inline]
+    pub fn decoded_count(&self) -> u64 {
+        self.decoder.decoded_count
+    }
 
-            self.buf_offset = 0;
-        }
+    /// Get the offsets in the base64 stream of the blocks which have been skipped because they
+    /// cannot be decoded, by a reader created with `new_recovering`. It is empty otherwise.
+    #[inline]
+    pub fn skipped_windows(&self) -> &[u64] {
+        self.decoder.skipped_windows.as_deref().unwrap_or_default()
+    }
 
-        self.buf_length -= distance;
+    /// Whether the base64 data ended on a complete 4-character block with correct padding,
+    /// rather than being cut short. It is `None` until the reader has returned EOF.
+    #[inline]
+    pub fn ended_cleanly(&self) -> Option<bool> {
+        self.decoder.ended_cleanly()
     }
 
+    /// Whether the inner reader has reached EOF and everything has been decoded and returned, so
+    /// that `read` returns `0` for any buffer. It tells the end of the stream apart from a read
+    /// with an empty buffer, which also returns `0`. It becomes `true` once a read with a
+    /// non-empty buffer has returned `0`.
     #[inline]
-    fn drain_temp<'a>(&mut self, buf: &'a mut [u8]) -> &'a mut [u8] {
-        debug_assert!(self.temp_length > 0);
-        debug_assert!(!buf.is_empty());
+    pub fn is_eof(&self) -> bool {
+        self.decoded.is_empty()
+            && self.decoder.is_drained()
+            && self.decoder.ended_cleanly().is_some()
+    }
 
-        let drain_length = buf.len().min(self.temp_length);
+    /// Get the number of decoded bytes and the time spent decoding them, which excludes the
+    /// time spent waiting for the inner reader.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn stats(&self) -> crate::DecodeStats {
+        self.decoder.stats()
+    }
 
-        unsafe {
-            copy_nonoverlapping(self.temp.as_ptr(), buf.as_mut_ptr(), drain_length);
-        }
+    /// Get the number of base64 bytes which have been read from the inner reader so far.
+    #[inline]
+    pub fn consumed_count(&self) -> u64 {
+        self.decoder.consumed_count
+    }
 
-        self.temp_length -= drain_length;
+    /// Get the number of plain bytes which can be returned without reading from the inner reader.
+    ///
+    /// It counts the decoded bytes buffered by `fill_buf`, the at most 2 decoded bytes left
+    /// behind by a too small read buffer, and 3 bytes for every complete 4-byte block of buffered
+    /// base64 data. Since the last block may be padded, this can be up to 2 more than what is
+    /// actually decoded. Base64 data which does not fill a block is not counted.
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.decoded.len() + self.decoder.buffered_len()
+    }
 
-        unsafe {
-            copy(
-                self.temp.as_ptr().add(self.temp_length),
-                self.temp.as_mut_ptr(),
-                self.temp_length,
-            );
-        }
+    /// Get the base64 data which has been read from the inner reader but not decoded yet, e.g. to
+    /// see why a stream stalls, such as 3 characters waiting for the 4th of their block. It is
+    /// what `into_inner` returns, after whitespaces and other skipped bytes are removed.
+    #[inline]
+    pub fn buffered_input(&self) -> &[u8] {
+        self.decoder.leftover()
+    }
+
+    /// Decode the rest of the stream and append it to `dst` like `read_to_string`, but directly
+    /// into the string's buffer, validating the decoded bytes as UTF-8 as they come in. A
+    /// character which is split between two reads is completed by the next one.
+    ///
+    /// If the decoded data is not valid UTF-8, an `InvalidData` error is returned and `dst` is
+    /// left unchanged. If reading fails otherwise, the characters decoded so far are kept.
+    pub fn read_to_utf8_string(&mut self, dst: &mut String) -> Result<usize, io::Error> {
+        let original_length = dst.len();
+
+        let mut buf = mem::take(dst).into_bytes();
+
+        // the bytes before `valid_length` are known to be valid UTF-8
+        let mut valid_length = original_length;
+
+        let result = loop {
+            let buf_length = buf.len();
+
+            buf.resize(buf_length + (N::USIZE >> 2) * 3, 0);
+
+            match self.read(&mut buf[buf_length..]) {
+                Ok(0) => {
+                    buf.truncate(buf_length);
+
+                    match str::from_utf8(&buf[valid_length..]) {
+                        Ok(_) => break Ok(buf_length - original_length),
+                        Err(e) => {
+                            buf.truncate(original_length);
+
+                            break Err(io::Error::new(ErrorKind::InvalidData, e));
+                        }
+                    }
+                }
+                Ok(c) => {
+                    buf.truncate(buf_length + c);
 
-        &mut buf[drain_length..]
+                    match str::from_utf8(&buf[valid_length..]) {
+                        Ok(_) => valid_length = buf.len(),
+                        // an incomplete character at the end may be completed by the next read
+                        Err(e) if e.error_len().is_none() => valid_length += e.valid_up_to(),
+                        Err(e) => {
+                            buf.truncate(original_length);
+
+                            break Err(io::Error::new(ErrorKind::InvalidData, e));
+                        }
+                    }
+                }
+                Err(e) => {
+                    buf.truncate(valid_length);
+
+                    break Err(e);
+                }
+            }
+        };
+
+        // SAFETY: `buf` has been truncated to the bytes which are validated as UTF-8
+        *dst = unsafe { String::from_utf8_unchecked(buf) };
+
+        result
     }
 
+    /// Read like `read`, but return a `ReadDecodedError` which tells invalid base64 data apart
+    /// from the errors of the inner reader.
     #[inline]
-    fn drain_block<'a>(&mut self, mut buf: &'a mut [u8]) -> Result<&'a mut [u8], DecodeError> {
-        debug_assert!(self.buf_length > 0);
-        debug_assert!(self.temp_length == 0);
-        debug_assert!(!buf.is_empty());
+    pub fn read_decoded(&mut self, buf: &mut [u8]) -> Result<usize, ReadDecodedError> {
+        Ok(self.read(buf)?)
+    }
+
+    /// Decode only the base64 data which has already been read from the inner reader, without
+    /// reading more, e.g. to flush what is decodable in an edge-triggered event loop. `0` is
+    /// returned when less than a 4-character block is buffered, which is not the end of the
+    /// stream, unlike for `read`.
+    pub fn read_buffered(&mut self, buf: &mut [u8]) -> Result<usize, DecoderError> {
+        if !self.decoded.is_empty() {
+            return Ok(self.decoded.drain_into(buf));
+        }
+
+        if self.decoder.needs_data() {
+            return Ok(0);
+        }
+
+        self.decoder.decode(buf)
+    }
 
-        let drain_length = self.buf_length.min(4);
+    /// Decode and append plain bytes to `buf` until the delimiter `delim` is found, like
+    /// `BufRead::read_until` over the decoded data. The delimiter is appended too, unless the end
+    /// of the stream is reached first. Returns the number of bytes appended.
+    ///
+    /// Data is decoded a byte at a time, so nothing after the delimiter is decoded except the rest
+    /// of its 3-byte block, which stays in the decoder. The base64 data after that block can still
+    /// be taken back with `into_inner`, e.g. to hand the stream over after a header line.
+    pub fn read_until_decoded(&mut self, delim: u8, buf: &mut Vec<u8>) -> Result<usize, io::Error> {
+        let original_buf_length = buf.len();
 
-        let mut b = [0; 3];
+        // the data already decoded by `fill_buf` or `peek` comes first
+        if !self.decoded.is_empty() {
+            let decoded = self.decoded.as_slice();
 
-        let decode_length = self.engine.decode_slice(
-            self.buf[self.buf_offset..(self.buf_offset + drain_length)].as_ref(),
-            &mut b,
-        ).map_err(|err| super::to_decode_error(err))?;
+            let (take_length, found) = match decoded.iter().position(|&b| b == delim) {
+                Some(i) => (i + 1, true),
+                None => (decoded.len(), false),
+            };
 
-        self.buf_left_shift(drain_length);
+            buf.extend_from_slice(&decoded[..take_length]);
 
-        let buf_length = buf.len();
+            self.decoded.consume(take_length);
 
-        if buf_length >= decode_length {
-            unsafe {
-                copy_nonoverlapping(b.as_ptr(), buf.as_mut_ptr(), decode_length);
+            if found {
+                return Ok(take_length);
             }
+        }
 
-            buf = &mut buf[decode_length..];
-        } else {
-            unsafe {
-                copy_nonoverlapping(b.as_ptr(), buf.as_mut_ptr(), buf_length);
+        let mut byte = [0u8; 1];
+
+        loop {
+            match self.decode_into(&mut byte) {
+                Ok(0) => break,
+                Ok(_) => {
+                    buf.push(byte[0]);
+
+                    if byte[0] == delim {
+                        break;
+                    }
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
             }
+        }
+
+        Ok(buf.len() - original_buf_length)
+    }
+
+    /// Decode ahead and return up to `n` upcoming plain bytes without consuming them, so that the
+    /// following reads return the same bytes. Fewer bytes are returned only at the end of the
+    /// stream.
+    ///
+    /// The bytes are kept in the buffer used by `fill_buf`, which grows if `n` is larger.
+    pub fn peek(&mut self, n: usize) -> Result<&[u8], io::Error> {
+        if self.decoded.len() < n {
+            let mut decoded = mem::take(&mut self.decoded);
+
+            decoded.buf.copy_within(decoded.offset..decoded.length, 0);
+
+            decoded.length -= decoded.offset;
+            decoded.offset = 0;
 
-            buf = &mut buf[buf_length..];
+            if decoded.buf.len() < n {
+                decoded.buf.resize(n.max(N::USIZE), 0);
+            }
 
-            self.temp_length = decode_length - buf_length;
+            while decoded.length < n {
+                match self.decode_into(&mut decoded.buf[decoded.length..]) {
+                    Ok(0) => break,
+                    Ok(c) => decoded.length += c,
+                    Err(e) => {
+                        self.decoded = decoded;
 
-            unsafe {
-                copy_nonoverlapping(
-                    b.as_ptr().add(buf_length),
-                    self.temp.as_mut_ptr(),
-                    self.temp_length,
-                );
+                        return Err(e);
+                    }
+                }
             }
+
+            self.decoded = decoded;
         }
 
-        Ok(buf)
+        let length = n.min(self.decoded.len());
+
+        Ok(&self.decoded.as_slice()[..length])
+    }
+
+    /// Replace the inner reader with a new one and clear all buffered data, so that this reader
+    /// can be reused for another base64 stream without reallocating its buffers.
+    pub fn reset(&mut self, reader: R) {
+        self.inner = reader;
+        self.decoder.reset();
+        self.decoded.clear();
     }
 
-    fn drain<'a>(&mut self, mut buf: &'a mut [u8]) -> Result<&'a mut [u8], DecodeError> {
-        if buf.is_empty() {
-            return Ok(buf);
+    /// Turn this reader into an iterator over the decoded bytes. Data is decoded chunk by chunk
+    /// into the buffer used by `fill_buf`, rather than with a read call per byte.
+    #[inline]
+    pub fn bytes_decoded(self) -> DecodedBytes<R, N> {
+        DecodedBytes {
+            reader: self,
         }
+    }
 
-        if self.temp_length > 0 {
-            buf = self.drain_temp(buf);
+    /// Create an adapter which stops after returning `n` decoded bytes. Unlike `Read::take`, which
+    /// limits the base64 data, it reads no more base64 data from the inner reader than the blocks
+    /// needed for those bytes, so the inner reader is left right after the last decoded block.
+    ///
+    /// Base64 data which this reader has already buffered is not given back to the inner reader.
+    /// `TakeDecoded::into_inner` returns it instead.
+    #[inline]
+    pub fn take_decoded(self, n: u64) -> TakeDecoded<R, N> {
+        TakeDecoded {
+            reader: self,
+            limit: n,
         }
+    }
 
-        debug_assert!(self.buf_length >= 4);
+    /// Decode the rest of the stream and write it to `dst`, returning the number of plain bytes
+    /// written. Decoded data is collected in a staging buffer of 16 decoding buffers' worth, so
+    /// `dst` gets a few large writes rather than one per buffer of base64 data.
+    ///
+    /// If reading fails, e.g. with `WouldBlock` on a non-blocking socket, the data decoded so far
+    /// is written to `dst` before the error is returned, so calling this again resumes the copy.
+    pub fn copy_to<W: Write>(&mut self, mut dst: W) -> Result<u64, io::Error> {
+        let mut written = 0;
 
-        let buf_length = buf.len();
+        if !self.decoded.is_empty() {
+            let decoded_length = self.decoded.len();
 
-        if buf_length >= 3 {
-            debug_assert!(self.temp_length == 0);
+            dst.write_all(self.decoded.as_slice())?;
 
-            let actual_max_read_size = (buf_length / 3) << 2; // (buf_length / 3) * 4
-            let max_available_self_buf_length = self.buf_length & !0b11;
+            written += decoded_length as u64;
 
-            let drain_length = max_available_self_buf_length.min(actual_max_read_size);
+            self.decoded.consume(decoded_length);
+        }
 
-            let decode_length = self.engine.decode_slice(
-                self.buf[self.buf_offset..(self.buf_offset + drain_length)].as_ref(),
-                &mut buf,
-            ).map_err(|err| super::to_decode_error(err))?;
-        
-            buf = &mut buf[decode_length..];
+        let mut staging = vec![0u8; (N::USIZE >> 2) * 3 * 16];
 
-            self.buf_left_shift(drain_length);
-        }
+        loop {
+            let mut staging_length = 0;
 
-        if !buf.is_empty() && self.buf_length >= 4 {
-            self.drain_block(buf)
-        } else {
-            Ok(buf)
+            while staging_length < staging.len() {
+                match self.decode_into(&mut staging[staging_length..]) {
+                    Ok(0) => break,
+                    Ok(c) => staging_length += c,
+                    Err(e) => {
+                        dst.write_all(&staging[..staging_length])?;
+
+                        return Err(e);
+                    }
+                }
+            }
+
+            if staging_length == 0 {
+                break;
+            }
+
+            dst.write_all(&staging[..staging_length])?;
+
+            written += staging_length as u64;
         }
+
+        dst.flush()?;
+
+        Ok(written)
     }
 
+    /// Unwrap this reader, returning the inner reader and the base64 data which has been read
+    /// from it but not decoded yet.
+    ///
+    /// Decoded data which has not been returned yet is discarded. That is the data buffered by
+    /// `fill_buf`, plus at most 2 bytes left behind when the last read buffer was too small to
+    /// hold a whole block.
     #[inline]
-    fn drain_end<'a>(&mut self, mut buf: &'a mut [u8]) -> Result<&'a mut [u8], DecodeError> {
-        if buf.is_empty() {
-            return Ok(buf);
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        let leftover = self.decoder.leftover().to_vec();
+
+        (self.inner, leftover)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Reader<R, N> {
+    /// Decode at most `max` plain bytes and append them to `dst`, returning how many bytes have
+    /// been appended. `0` is returned at the end of the base64 stream.
+    ///
+    /// The data is decoded into `dst` directly, after growing it by a buffer's worth of decoded
+    /// data, instead of being copied out of another buffer.
+    pub fn read_to_bytes(&mut self, dst: &mut BytesMut, max: usize) -> Result<usize, io::Error> {
+        if !self.decoded.is_empty() {
+            let decoded = self.decoded.as_slice();
+
+            let drain_length = decoded.len().min(max);
+
+            dst.extend_from_slice(&decoded[..drain_length]);
+
+            self.consume(drain_length);
+
+            return Ok(drain_length);
         }
 
-        if self.temp_length > 0 {
-            buf = self.drain_temp(buf);
+        // a full buffer of base64 data is decoded into at most 3/4 of its size
+        let length = max.min((N::USIZE >> 2) * 3);
+
+        let dst_length = dst.len();
+
+        dst.resize(dst_length + length, 0);
+
+        match self.decode_into(&mut dst[dst_length..]) {
+            Ok(c) => {
+                dst.truncate(dst_length + c);
+
+                Ok(c)
+            }
+            Err(e) => {
+                dst.truncate(dst_length);
+
+                Err(e)
+            }
         }
+    }
+}
 
-        if !buf.is_empty() && self.buf_length > 0 {
-            self.drain_block(buf)
-        } else {
-            Ok(buf)
+impl<R: Read + Seek, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>
+    FromBase64Reader<R, N>
+{
+    /// Move to the plain byte at `pos`, with the base64 data starting at the beginning of the
+    /// inner reader, or after the bytes skipped by `new_skipping`. The inner reader is moved to
+    /// the 4-byte block containing `pos`, so only that block is decoded rather than everything
+    /// before it.
+    ///
+    /// An `Unsupported` error is returned if whitespaces or other bytes are ignored, or a data
+    /// URI prefix is skipped, because the base64 data is then not at a fixed ratio to the plain
+    /// data.
+    pub fn seek_decoded(&mut self, pos: u64) -> Result<(), io::Error> {
+        if !self.decoder.is_seekable() {
+            return Err(io::Error::new(
+                ErrorKind::Unsupported,
+                "cannot seek in base64 data with whitespaces or a data URI prefix",
+            ));
+        }
+
+        let block = pos / 3;
+
+        self.inner.seek(SeekFrom::Start(self.decoder.skip_bytes + block * 4))?;
+
+        self.decoder.seek_block(block);
+        self.decoded.clear();
+
+        // the bytes before `pos` in its block are decoded and discarded
+        let mut skipped = [0u8; 2];
+        let mut skip_length = (pos % 3) as usize;
+
+        while skip_length > 0 {
+            match self.read(&mut skipped[..skip_length]) {
+                Ok(0) => break,
+                Ok(c) => skip_length -= c,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Reader<R, N> {
+    fn check_first_buffer(&mut self) -> Result<(), io::Error> {
+        loop {
+            match self.inner.read(self.decoder.spare()) {
+                Ok(c) => {
+                    self.decoder.fill(c)?;
+
+                    break;
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
         }
+
+        Ok(self.decoder.check_symbols()?)
+    }
+
+    fn decode_into(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        while self.decoder.needs_data() {
+            match self.inner.read(self.decoder.spare()) {
+                Ok(0) => return Ok(self.decoder.decode_end(buf)?),
+                Ok(c) => self.decoder.fill(c)?,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(self.decoder.decode(buf)?)
     }
 }
 
 impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
     for FromBase64Reader<R, N>
 {
-    fn read(&mut self, mut buf: &mut [u8]) -> Result<usize, io::Error> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if !self.decoded.is_empty() {
+            return Ok(self.decoded.drain_into(buf));
+        }
+
+        self.decode_into(buf)
+    }
+
+    /// Decode directly into the cursor. Only the part which this call can decode into is
+    /// zeroed, rather than the whole uninitialized capacity of the cursor.
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, mut cursor: BorrowedCursor<'_>) -> Result<(), io::Error> {
+        if !self.decoded.is_empty() {
+            let decoded = self.decoded.as_slice();
+
+            let drain_length = decoded.len().min(cursor.capacity());
+
+            cursor.append(&decoded[..drain_length]);
+
+            self.consume(drain_length);
+
+            return Ok(());
+        }
+
+        // a full buffer of base64 data is decoded into at most 3/4 of its size
+        let length = cursor.capacity().min((N::USIZE >> 2) * 3);
+
+        // SAFETY: the bytes are only written here, never de-initialized
+        let spare = unsafe { &mut cursor.as_mut()[..length] };
+
+        spare.fill(MaybeUninit::new(0));
+
+        // SAFETY: all `length` bytes have just been initialized
+        let buf = unsafe { slice::from_raw_parts_mut(spare.as_mut_ptr().cast::<u8>(), length) };
+
+        let c = self.decode_into(buf)?;
+
+        // SAFETY: the first `c` bytes have been filled with decoded data
+        unsafe { cursor.advance(c) };
+
+        Ok(())
+    }
+
+    /// Decode directly into the spare space of `buf`, a buffer's worth of base64 data at a time.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize, io::Error> {
         let original_buf_length = buf.len();
 
-        while self.buf_length < 4 {
-            match self.inner.read(&mut self.buf[(self.buf_offset + self.buf_length)..]) {
+        if !self.decoded.is_empty() {
+            buf.extend_from_slice(self.decoded.as_slice());
+
+            self.decoded.consume(self.decoded.len());
+        }
+
+        loop {
+            let buf_length = buf.len();
+
+            // a full buffer of base64 data is decoded into at most 3/4 of its size
+            buf.resize(buf_length + (N::USIZE >> 2) * 3, 0);
+
+            match self.decode_into(&mut buf[buf_length..]) {
                 Ok(0) => {
-                    buf =
-                        self.drain_end(buf).map_err(|err| io::Error::new(ErrorKind::Other, err))?;
+                    buf.truncate(buf_length);
 
-                    return Ok(original_buf_length - buf.len());
+                    break;
+                }
+                Ok(c) => buf.truncate(buf_length + c),
+                Err(e) => {
+                    buf.truncate(buf_length);
+
+                    return Err(e);
                 }
-                Ok(c) => self.buf_length += c,
-                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
-                Err(e) => return Err(e),
             }
         }
 
-        buf = self.drain(buf).map_err(|err| io::Error::new(ErrorKind::Other, err))?;
+        Ok(buf.len() - original_buf_length)
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> BufRead
+    for FromBase64Reader<R, N>
+{
+    fn fill_buf(&mut self) -> Result<&[u8], io::Error> {
+        if self.decoded.is_empty() {
+            let mut decoded = mem::take(&mut self.decoded);
+
+            let result = self.decode_into(decoded.refill(N::USIZE));
+
+            if let Ok(c) = result {
+                decoded.filled(c);
+            }
+
+            self.decoded = decoded;
+
+            result?;
+        }
+
+        Ok(self.decoded.as_slice())
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.decoded.consume(amt);
+    }
+}
+
+/// Decode all the standard base64 data of `src` and write it to `dst`, returning the number of
+/// plain bytes written. It is `FromBase64Reader::copy_to`, which writes in larger chunks than
+/// `io::copy` from a `FromBase64Reader`.
+#[inline]
+pub fn decode_copy<R: Read, W: Write>(src: R, dst: W) -> Result<u64, io::Error> {
+    FromBase64Reader::new(src).copy_to(dst)
+}
+
+/// An iterator over the decoded bytes of a `FromBase64Reader`, created by `bytes_decoded`.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct DecodedBytes<
+    R: Read,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    reader: FromBase64Reader<R, N>,
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Iterator
+    for DecodedBytes<R, N>
+{
+    type Item = Result<u8, io::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let b = match self.reader.fill_buf() {
+            Ok(decoded) => *decoded.first()?,
+            Err(e) => return Some(Err(e)),
+        };
+
+        self.reader.consume(1);
 
-        Ok(original_buf_length - buf.len())
+        Some(Ok(b))
+    }
+}
+
+/// A reader which returns at most a given number of decoded bytes of a `FromBase64Reader`,
+/// created by `take_decoded`.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct TakeDecoded<
+    R: Read,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    reader: FromBase64Reader<R, N>,
+    limit: u64,
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> TakeDecoded<R, N> {
+    /// Get the number of decoded bytes which can still be returned.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Unwrap this adapter, returning the inner reader and the base64 data which has been read
+    /// from it but not decoded yet, like `FromBase64Reader::into_inner`.
+    #[inline]
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
+    for TakeDecoded<R, N>
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if self.limit == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let buf_length = self.limit.min(buf.len() as u64) as usize;
+
+        let buf = &mut buf[..buf_length];
+
+        let reader = &mut self.reader;
+
+        let c = if !reader.decoded.is_empty() {
+            reader.read(buf)?
+        } else {
+            loop {
+                if !reader.decoder.needs_data() {
+                    break reader.decoder.decode(buf)?;
+                }
+
+                // only the blocks which hold the rest of the decoded bytes are read
+                let wanted = self
+                    .limit
+                    .div_ceil(3)
+                    .saturating_mul(4)
+                    .saturating_sub(reader.decoder.leftover().len() as u64)
+                    .max(1);
+
+                let spare = reader.decoder.spare();
+
+                let spare_length = (spare.len() as u64).min(wanted) as usize;
+
+                match reader.inner.read(&mut spare[..spare_length]) {
+                    Ok(0) => break reader.decoder.decode_end(buf)?,
+                    Ok(c) => reader.decoder.fill(c)?,
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        self.limit -= c as u64;
+
+        Ok(c)
     }
 }
 
@@ -228,3 +1105,23 @@ impl<R: Read> From<R> for FromBase64Reader<R> {
         FromBase64Reader::new(reader)
     }
 }
+
+/// Create a reader over the default inner reader, e.g. an empty `Cursor`, with the options of
+/// `new` and the buffer size `N`.
+impl<R: Read + Default, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Default
+    for FromBase64Reader<R, N>
+{
+    #[inline]
+    fn default() -> Self {
+        FromBase64Reader::new2(R::default(), &base64::engine::general_purpose::STANDARD)
+    }
+}
+
+/// Decode an in-memory base64 string. Byte slices are readers themselves, so
+/// `FromBase64Reader::from(&b"SGVsbG8="[..])` works through the `From<R>` implementation.
+impl<'a> From<&'a str> for FromBase64Reader<&'a [u8]> {
+    #[inline]
+    fn from(base64: &'a str) -> Self {
+        FromBase64Reader::new(base64.as_bytes())
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::{self, ErrorKind, Read};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4};
+use generic_array::ArrayLength;
+
+use crate::from_base64_reader::{STANDARD_INDIFFERENT_PAD, URL_SAFE_INDIFFERENT_PAD};
+use crate::FromBase64Reader;
+
+/// Configure a `FromBase64Reader` with any combination of the decoding options, for which the
+/// `new_*` constructors only offer one at a time.
+///
+/// ```rust
+/// use std::io::Read;
+///
+/// use base64_stream::FromBase64ReaderBuilder;
+///
+/// let mut reader = FromBase64ReaderBuilder::new()
+///     .url_safe(true)
+///     .no_pad(true)
+///     .ignore_whitespace(true)
+///     .limit(1024)
+///     .build(b"SGkg\ndGhlcmU_".as_ref())
+///     .unwrap();
+///
+/// let mut test_data = Vec::new();
+///
+/// reader.read_to_end(&mut test_data).unwrap();
+///
+/// assert_eq!(b"Hi there?".as_ref(), test_data);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FromBase64ReaderBuilder {
+    url_safe: bool,
+    no_pad: bool,
+    ignore_whitespace: bool,
+    lenient: bool,
+    detect_alphabet: bool,
+    data_uri: bool,
+    nul_padded: bool,
+    stop_at_padding: bool,
+    recovering: bool,
+    strict: bool,
+    pad: Option<u8>,
+    skip_bytes: u64,
+    limit: Option<u64>,
+    crc32: Option<u32>,
+    max_inner_read: Option<usize>,
+}
+
+impl FromBase64ReaderBuilder {
+    /// Create a builder with the options of `FromBase64Reader::new`.
+    #[inline]
+    pub fn new() -> FromBase64ReaderBuilder {
+        FromBase64ReaderBuilder::default()
+    }
+
+    /// Decode the URL-safe alphabet instead of the standard one.
+    #[inline]
+    pub fn url_safe(mut self, url_safe: bool) -> Self {
+        self.url_safe = url_safe;
+
+        self
+    }
+
+    /// Accept base64 data whose trailing padding is omitted.
+    #[inline]
+    pub fn no_pad(mut self, no_pad: bool) -> Self {
+        self.no_pad = no_pad;
+
+        self
+    }
+
+    /// Skip whitespaces, like `FromBase64Reader::new_ignoring_whitespace`.
+    #[inline]
+    pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+
+        self
+    }
+
+    /// Skip every byte which is not a base64 character, like `FromBase64Reader::new_lenient`.
+    #[inline]
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+
+        self
+    }
+
+    /// Pick the alphabet by the data, like `FromBase64Reader::new_auto`.
+    #[inline]
+    pub fn detect_alphabet(mut self, detect_alphabet: bool) -> Self {
+        self.detect_alphabet = detect_alphabet;
+
+        self
+    }
+
+    /// Skip a data URI prefix, like `FromBase64Reader::new_data_uri`.
+    #[inline]
+    pub fn data_uri(mut self, data_uri: bool) -> Self {
+        self.data_uri = data_uri;
+
+        self
+    }
+
+    /// End the data at trailing NULs, like `FromBase64Reader::new_nul_padded`.
+    #[inline]
+    pub fn nul_padded(mut self, nul_padded: bool) -> Self {
+        self.nul_padded = nul_padded;
+
+        self
+    }
+
+    /// End the data at the first padded block, like `FromBase64Reader::new_stop_at_padding`.
+    #[inline]
+    pub fn stop_at_padding(mut self, stop_at_padding: bool) -> Self {
+        self.stop_at_padding = stop_at_padding;
+
+        self
+    }
+
+    /// Skip the blocks which cannot be decoded, like `FromBase64Reader::new_recovering`. This is
+    /// lossy, so the skipped blocks must be checked with `FromBase64Reader::skipped_windows`.
+    #[inline]
+    pub fn recovering(mut self, recovering: bool) -> Self {
+        self.recovering = recovering;
+
+        self
+    }
+
+    /// Accept only canonical base64 data, which is the default. It makes `build` reject the
+    /// options which tolerate other data: `ignore_whitespace`, `lenient`, `no_pad` and
+    /// `recovering`.
+    #[inline]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+
+        self
+    }
+
+    /// Use `pad` as the padding character, like `FromBase64Reader::new_with_padding`.
+    #[inline]
+    pub fn padding(mut self, pad: u8) -> Self {
+        self.pad = Some(pad);
+
+        self
+    }
+
+    /// Drop the first `skip_bytes` bytes, like `FromBase64Reader::new_skipping`.
+    #[inline]
+    pub fn skip_bytes(mut self, skip_bytes: u64) -> Self {
+        self.skip_bytes = skip_bytes;
+
+        self
+    }
+
+    /// Decode at most `max_bytes` bytes, like `FromBase64Reader::with_limit`.
+    #[inline]
+    pub fn limit(mut self, max_bytes: u64) -> Self {
+        self.limit = Some(max_bytes);
+
+        self
+    }
+
+    /// Check the decoded data against a CRC-32 checksum, like `FromBase64Reader::with_crc32`.
+    #[inline]
+    pub fn crc32(mut self, expected: u32) -> Self {
+        self.crc32 = Some(expected);
+
+        self
+    }
+
+    /// Read at most `max_bytes` bytes from the inner reader at a time, instead of as much as the
+    /// buffer can hold. With a small value, e.g. for an interactive `TcpStream`, the first bytes
+    /// are decoded sooner, at the cost of more reads.
+    #[inline]
+    pub fn max_inner_read(mut self, max_bytes: usize) -> Self {
+        self.max_inner_read = Some(max_bytes);
+
+        self
+    }
+
+    /// Create the reader with the default buffer size.
+    ///
+    /// An `InvalidInput` error is returned if the options do not fit together.
+    #[inline]
+    pub fn build<R: Read>(&self, reader: R) -> Result<FromBase64Reader<R>, io::Error> {
+        self.build2(reader)
+    }
+
+    /// Create the reader with the buffer size `N`.
+    ///
+    /// An `InvalidInput` error is returned if the options do not fit together.
+    pub fn build2<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>(
+        &self,
+        reader: R,
+    ) -> Result<FromBase64Reader<R, N>, io::Error> {
+        if self.strict && (self.ignore_whitespace || self.lenient || self.no_pad || self.recovering)
+        {
+            return Err(invalid_input("strict mode does not tolerate non-canonical base64 data"));
+        }
+
+        // the detected alphabet is always decoded with the padding required
+        if self.detect_alphabet && (self.url_safe || self.no_pad) {
+            return Err(invalid_input("the alphabet cannot be both detected and chosen"));
+        }
+
+        let (alphabet, engine) = match (self.url_safe, self.no_pad) {
+            (false, false) => {
+                (&base64::alphabet::STANDARD, &base64::engine::general_purpose::STANDARD)
+            }
+            (false, true) => (&base64::alphabet::STANDARD, &STANDARD_INDIFFERENT_PAD),
+            (true, false) => {
+                (&base64::alphabet::URL_SAFE, &base64::engine::general_purpose::URL_SAFE)
+            }
+            (true, true) => (&base64::alphabet::URL_SAFE, &URL_SAFE_INDIFFERENT_PAD),
+        };
+
+        if self.max_inner_read == Some(0) {
+            return Err(invalid_input("the inner reads must be allowed to read at least 1 byte"));
+        }
+
+        if let Some(pad) = self.pad {
+            if alphabet.as_str().as_bytes().contains(&pad) {
+                return Err(invalid_input("the padding character is a base64 character"));
+            }
+        }
+
+        let mut reader = FromBase64Reader::new2(reader, engine);
+
+        let decoder = &mut reader.decoder;
+
+        decoder.ignore_whitespace = self.ignore_whitespace;
+        decoder.ignore_invalid = self.lenient;
+        decoder.detect_alphabet = self.detect_alphabet;
+        decoder.nul_padded = self.nul_padded;
+        decoder.stop_at_padding = self.stop_at_padding;
+        decoder.limit = self.limit;
+        decoder.max_inner_read = self.max_inner_read;
+        decoder.crc32 = self.crc32.map(|expected| (expected, 0));
+
+        if let Some(pad) = self.pad {
+            decoder.pad = pad;
+        }
+
+        if self.recovering {
+            decoder.skipped_windows = Some(Vec::new());
+        }
+
+        if self.data_uri {
+            decoder.set_strip_data_uri();
+        }
+
+        decoder.set_skip_bytes(self.skip_bytes);
+
+        Ok(reader)
+    }
+}
+
+#[inline]
+fn invalid_input(message: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
@@ -1,4 +1,3 @@
-use std::intrinsics::copy_nonoverlapping;
 use std::io::{self, ErrorKind, Write};
 
 use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
@@ -8,6 +7,8 @@ use base64::{self,
     Engine,
 };
 
+const TAKEN: &str = "the inner writer is only taken out by `finish`";
+
 /// Write base64 data and decode them to plain data.
 #[derive(Educe)]
 #[educe(Debug)]
@@ -15,10 +16,13 @@ pub struct FromBase64Writer<
     W: Write,
     N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
 > {
+    /// Only taken out by `finish`.
     #[educe(Debug(ignore))]
-    inner: W,
+    inner: Option<W>,
     buf: [u8; 4],
     buf_length: usize,
+    /// Only a scratch buffer for decoding, so it holds no state worth showing.
+    #[educe(Debug(ignore))]
     temp: GenericArray<u8, N>,
     #[educe(Debug(ignore))]
     engine: &'static base64::engine::general_purpose::GeneralPurpose,
@@ -35,7 +39,7 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBas
     #[inline]
     pub fn new2(writer: W, engine: &'static base64::engine::general_purpose::GeneralPurpose) -> FromBase64Writer<W, N> {
         FromBase64Writer {
-            inner: writer,
+            inner: Some(writer),
             buf: [0; 4],
             buf_length: 0,
             temp: GenericArray::default(),
@@ -44,6 +48,23 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBas
     }
 }
 
+impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Writer<W, N> {
+    /// Decode the last incomplete block, flush the inner writer and return it. An error is
+    /// returned if the base64 data ends with a block which cannot be complete, e.g. a single
+    /// character.
+    ///
+    /// Nothing can be written after calling this method, because the writer is consumed.
+    pub fn finish(mut self) -> Result<W, io::Error> {
+        self.flush()?;
+
+        let mut inner = self.inner.take().expect(TAKEN);
+
+        inner.flush()?;
+
+        Ok(inner)
+    }
+}
+
 impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBase64Writer<W, N> {
     fn drain_block(&mut self) -> Result<(), io::Error> {
         debug_assert!(self.buf_length > 0);
@@ -54,7 +75,7 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> FromBas
         )
         .map_err(|err| io::Error::new(ErrorKind::Other, err))?;
 
-        self.inner.write_all(&self.temp[..decode_length])?;
+        self.inner.as_mut().expect(TAKEN).write_all(&self.temp[..decode_length])?;
 
         self.buf_length = 0;
 
@@ -80,15 +101,13 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write
 
                 buf = &buf[max_available_buf_length..];
 
-                self.inner.write_all(&self.temp[..decode_length])?;
+                self.inner.as_mut().expect(TAKEN).write_all(&self.temp[..decode_length])?;
             }
 
             let buf_length = buf.len();
 
             if buf_length > 0 {
-                unsafe {
-                    copy_nonoverlapping(buf.as_ptr(), self.buf.as_mut_ptr(), buf_length);
-                }
+                self.buf[..buf_length].copy_from_slice(buf);
 
                 self.buf_length = buf_length;
             }
@@ -101,13 +120,8 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write
 
             let drain_length = r.min(buf_length);
 
-            unsafe {
-                copy_nonoverlapping(
-                    buf.as_ptr(),
-                    self.buf.as_mut_ptr().add(self.buf_length),
-                    drain_length,
-                );
-            }
+            self.buf[self.buf_length..(self.buf_length + drain_length)]
+                .copy_from_slice(&buf[..drain_length]);
 
             buf = &buf[drain_length..];
 
@@ -135,6 +149,17 @@ impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Drop
+    for FromBase64Writer<W, N>
+{
+    #[inline]
+    fn drop(&mut self) {
+        crate::wipe(&mut self.buf);
+        crate::wipe(&mut self.temp);
+    }
+}
+
 impl<W: Write> From<W> for FromBase64Writer<W> {
     #[inline]
     fn from(reader: W) -> Self {
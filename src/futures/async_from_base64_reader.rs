@@ -0,0 +1,124 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::ArrayLength;
+
+use futures_io::{AsyncBufRead, AsyncRead};
+
+use crate::decoded_buffer::DecodedBuffer;
+use crate::decoder::Decoder;
+
+/// Read base64 data from an asynchronous reader and decode them to plain data.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct AsyncFromBase64Reader<
+    R: AsyncRead + Unpin,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    decoder: Decoder<N>,
+    decoded: DecodedBuffer,
+}
+
+impl<R: AsyncRead + Unpin> AsyncFromBase64Reader<R> {
+    #[inline]
+    pub fn new(reader: R) -> AsyncFromBase64Reader<R> {
+        Self::new2(reader, &base64::engine::general_purpose::STANDARD)
+    }
+}
+
+impl<R: AsyncRead + Unpin, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>
+    AsyncFromBase64Reader<R, N>
+{
+    #[inline]
+    pub fn new2(
+        reader: R,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> AsyncFromBase64Reader<R, N> {
+        AsyncFromBase64Reader {
+            inner: reader,
+            decoder: Decoder::new(engine),
+            decoded: DecodedBuffer::default(),
+        }
+    }
+
+    /// Get a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this reader, returning the inner reader and the base64 data which has been read
+    /// from it but not decoded yet. Decoded data buffered by `poll_fill_buf` is discarded.
+    #[inline]
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        let leftover = self.decoder.leftover().to_vec();
+
+        (self.inner, leftover)
+    }
+}
+
+impl<R: AsyncRead + Unpin, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> AsyncRead
+    for AsyncFromBase64Reader<R, N>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let this = self.get_mut();
+
+        if !this.decoded.is_empty() {
+            return Poll::Ready(Ok(this.decoded.drain_into(buf)));
+        }
+
+        let inner = &mut this.inner;
+
+        this.decoder.poll_decode(buf, |spare| Pin::new(&mut *inner).poll_read(cx, spare))
+    }
+}
+
+impl<R: AsyncRead + Unpin, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> AsyncBufRead
+    for AsyncFromBase64Reader<R, N>
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8], io::Error>> {
+        let this = self.get_mut();
+
+        if this.decoded.is_empty() {
+            let inner = &mut this.inner;
+
+            let poll = this.decoder.poll_decode(this.decoded.refill(N::USIZE), |spare| {
+                Pin::new(&mut *inner).poll_read(cx, spare)
+            });
+
+            match poll {
+                Poll::Ready(Ok(decode_length)) => this.decoded.filled(decode_length),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(Ok(this.decoded.as_slice()))
+    }
+
+    #[inline]
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().decoded.consume(amt);
+    }
+}
+
+impl<R: AsyncRead + Unpin> From<R> for AsyncFromBase64Reader<R> {
+    #[inline]
+    fn from(reader: R) -> Self {
+        AsyncFromBase64Reader::new(reader)
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{self, ErrorKind, Read};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::ArrayLength;
+
+use crate::{FromBase64Reader, ToBase64Reader};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Read hex data and convert them to standard base64 data in one pass.
+///
+/// Hex digits may be in either case. An odd number of digits is an error at the end of the stream.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct HexToBase64Reader<
+    R: Read,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    inner: ToBase64Reader<HexDecoder<R>, N>,
+}
+
+impl<R: Read> HexToBase64Reader<R> {
+    #[inline]
+    pub fn new(reader: R) -> HexToBase64Reader<R> {
+        Self::new2(reader, &base64::engine::general_purpose::STANDARD)
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> HexToBase64Reader<R, N> {
+    #[inline]
+    pub fn new2(
+        reader: R,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> HexToBase64Reader<R, N> {
+        HexToBase64Reader {
+            inner: ToBase64Reader::new2(HexDecoder::new(reader), engine),
+        }
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
+    for HexToBase64Reader<R, N>
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        self.inner.read(buf)
+    }
+}
+
+/// Read standard base64 data and convert them to lowercase hex data in one pass.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Base64ToHexReader<
+    R: Read,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    inner: HexEncoder<FromBase64Reader<R, N>>,
+}
+
+impl<R: Read> Base64ToHexReader<R> {
+    #[inline]
+    pub fn new(reader: R) -> Base64ToHexReader<R> {
+        Self::new2(reader, &base64::engine::general_purpose::STANDARD)
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Base64ToHexReader<R, N> {
+    #[inline]
+    pub fn new2(
+        reader: R,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> Base64ToHexReader<R, N> {
+        Base64ToHexReader {
+            inner: HexEncoder::new(FromBase64Reader::new2(reader, engine)),
+        }
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
+    for Base64ToHexReader<R, N>
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        self.inner.read(buf)
+    }
+}
+
+/// Decode hex data read from the inner reader.
+#[derive(Educe)]
+#[educe(Debug)]
+struct HexDecoder<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    /// The high nibble of a byte whose second digit has not been read yet.
+    high: Option<u8>,
+    consumed_count: u64,
+}
+
+impl<R: Read> HexDecoder<R> {
+    #[inline]
+    fn new(reader: R) -> HexDecoder<R> {
+        HexDecoder {
+            inner: reader,
+            high: None,
+            consumed_count: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for HexDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let c = match self.inner.read(buf) {
+                Ok(0) if self.high.is_some() => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "the hex data has an odd number of digits",
+                    ));
+                }
+                Ok(0) => return Ok(0),
+                Ok(c) => c,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            let mut length = 0;
+
+            // the digits are decoded in place, since every byte is written behind its digits
+            for i in 0..c {
+                let nibble = match buf[i] {
+                    b @ b'0'..=b'9' => b - b'0',
+                    b @ b'a'..=b'f' => b - b'a' + 10,
+                    b @ b'A'..=b'F' => b - b'A' + 10,
+                    b => {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "invalid hex digit {:?} at byte {}",
+                                b as char,
+                                self.consumed_count + i as u64
+                            ),
+                        ));
+                    }
+                };
+
+                match self.high.take() {
+                    Some(high) => {
+                        buf[length] = (high << 4) | nibble;
+
+                        length += 1;
+                    }
+                    None => self.high = Some(nibble),
+                }
+            }
+
+            self.consumed_count += c as u64;
+
+            if length > 0 {
+                return Ok(length);
+            }
+        }
+    }
+}
+
+/// Encode data read from the inner reader to lowercase hex data.
+#[derive(Educe)]
+#[educe(Debug)]
+struct HexEncoder<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    /// The second digit of a byte, left behind by a one-byte read buffer.
+    low: Option<u8>,
+}
+
+impl<R: Read> HexEncoder<R> {
+    #[inline]
+    fn new(reader: R) -> HexEncoder<R> {
+        HexEncoder {
+            inner: reader,
+            low: None,
+        }
+    }
+}
+
+impl<R: Read> Read for HexEncoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(low) = self.low.take() {
+            buf[0] = low;
+
+            return Ok(1);
+        }
+
+        if buf.len() == 1 {
+            let mut b = [0u8; 1];
+
+            if self.inner.read(&mut b)? == 0 {
+                return Ok(0);
+            }
+
+            buf[0] = HEX_DIGITS[(b[0] >> 4) as usize];
+
+            self.low = Some(HEX_DIGITS[(b[0] & 0xF) as usize]);
+
+            return Ok(1);
+        }
+
+        let read_length = buf.len() >> 1;
+
+        let c = self.inner.read(&mut buf[..read_length])?;
+
+        // the bytes are expanded in place from the back, so no byte is overwritten before it is
+        // encoded
+        for i in (0..c).rev() {
+            let b = buf[i];
+
+            buf[i * 2] = HEX_DIGITS[(b >> 4) as usize];
+            buf[i * 2 + 1] = HEX_DIGITS[(b & 0xF) as usize];
+        }
+
+        Ok(c * 2)
+    }
+}
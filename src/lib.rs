@@ -59,7 +59,7 @@ assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGlu
 ```rust
 use std::io::Cursor;
 
-use std::io::Read;
+use std::io::{self, Read};
 
 use base64_stream::FromBase64Reader;
 
@@ -123,24 +123,443 @@ reader.read_to_string(&mut base64).unwrap();
 
 assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==", base64);
 ```
+
+## Tokio
+
+Enable the `tokio` feature to get the `tokio` module, which provides `AsyncFromBase64Reader` implementing `tokio::io::AsyncRead` and `AsyncToBase64Writer` implementing `tokio::io::AsyncWrite`.
+
+## futures
+
+Enable the `futures` feature to get the `futures` module, which provides the same types implementing `futures::io::AsyncRead` and `futures::io::AsyncWrite`, for runtimes such as async-std and smol.
+
+## no_std
+
+Disable the default `std` feature to use the crate without `std`. Only `Decoder`, the state machine behind the base64 readers, is available then; it decodes base64 data which is read into its buffer manually.
+
+```rust
+use base64_stream::Decoder;
+
+let mut base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+let mut decoder: Decoder = Decoder::new(&base64::engine::general_purpose::STANDARD);
+
+let mut buf = [0u8; 32];
+let mut test_data = Vec::new();
+
+loop {
+    let c = if !decoder.needs_data() {
+        decoder.decode(&mut buf).unwrap()
+    } else if base64.is_empty() {
+        decoder.decode_end(&mut buf).unwrap()
+    } else {
+        let spare = decoder.spare();
+        let length = spare.len().min(base64.len());
+
+        spare[..length].copy_from_slice(&base64[..length]);
+        base64 = &base64[length..];
+
+        decoder.fill(length).unwrap();
+
+        continue;
+    };
+
+    if c == 0 {
+        break;
+    }
+
+    test_data.extend_from_slice(&buf[..c]);
+}
+
+assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref(), test_data);
+```
+
+## bytes
+
+Enable the `bytes` feature to get `FromBase64Reader::read_to_bytes`, which decodes into the spare capacity of a `bytes::BytesMut` without going through another buffer.
+
+## zeroize
+
+Enable the `zeroize` feature to overwrite the buffers of `Decoder`, `FromBase64Reader` and `FromBase64Writer` with zeros when they are dropped, e.g. when decoding credentials.
+
+## read_buf
+
+On a nightly compiler, enable the `read_buf` feature to let `FromBase64Reader` implement `Read::read_buf`, which decodes into uninitialized buffers without zero-filling them as a whole first.
+
+## parallel
+
+Enable the `parallel` feature to get `decode_file_parallel`, which decodes a large file of unwrapped base64 data with a thread per CPU core and writes the plain data in order.
+
+## metrics
+
+Enable the `metrics` feature to get `FromBase64Reader::stats`, which returns the number of decoded bytes and the time spent in the base64 engine, excluding the reads from the inner reader, e.g. to compare buffer sizes.
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "read_buf", feature(read_buf, core_io_borrowed_buf))]
+
 pub extern crate base64;
 pub extern crate generic_array;
 
 #[macro_use]
 extern crate educe;
 
+mod crc32;
+#[cfg(feature = "metrics")]
+mod decode_stats;
+#[cfg(feature = "std")]
+mod decoded_buffer;
+mod decoder;
+mod decoder_error;
+#[cfg(feature = "std")]
+mod document_reader;
+#[cfg(feature = "std")]
+mod encoder;
+#[cfg(feature = "std")]
+mod frame_reader;
+mod from_base64_error;
+#[cfg(feature = "std")]
 mod from_base64_reader;
+#[cfg(feature = "std")]
+mod from_base64_reader_builder;
+#[cfg(feature = "std")]
 mod from_base64_writer;
+#[cfg(feature = "std")]
+mod hex_reader;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "std")]
+mod quoted_printable_reader;
+#[cfg(feature = "std")]
+mod read_decoded_error;
+#[cfg(feature = "std")]
+mod repad_reader;
+#[cfg(feature = "std")]
 mod to_base64_reader;
+#[cfg(feature = "std")]
+mod transcode_reader;
+#[cfg(feature = "std")]
+mod to_base64_string_writer;
+#[cfg(feature = "std")]
 mod to_base64_writer;
 
+#[cfg(feature = "futures")]
+pub mod futures;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+#[cfg(feature = "metrics")]
+pub use decode_stats::*;
+pub use decoder::Decoder;
+pub use decoder_error::*;
+#[cfg(feature = "std")]
+pub use document_reader::*;
+#[cfg(feature = "std")]
+pub use frame_reader::*;
+pub use from_base64_error::*;
+#[cfg(feature = "std")]
 pub use from_base64_reader::*;
+#[cfg(feature = "std")]
+pub use from_base64_reader_builder::*;
+#[cfg(feature = "std")]
 pub use from_base64_writer::*;
+#[cfg(feature = "std")]
+pub use hex_reader::*;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
+#[cfg(feature = "std")]
+pub use quoted_printable_reader::*;
+#[cfg(feature = "std")]
+pub use read_decoded_error::*;
+#[cfg(feature = "std")]
+pub use repad_reader::*;
+#[cfg(feature = "std")]
 pub use to_base64_reader::*;
+#[cfg(feature = "std")]
+pub use to_base64_string_writer::*;
+#[cfg(feature = "std")]
 pub use to_base64_writer::*;
+#[cfg(feature = "std")]
+pub use transcode_reader::*;
+
+#[cfg(feature = "std")]
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Decode all base64 data from a reader.
+#[cfg(feature = "std")]
+#[inline]
+pub fn decode_to_vec<R: Read>(reader: R) -> Result<Vec<u8>, io::Error> {
+    let mut data = Vec::new();
+
+    FromBase64Reader::new(reader).read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+/// Check that all the data of a reader is base64 data which `engine` can decode, including the
+/// placement of the padding, without keeping the decoded data. The decoded data go through a
+/// fixed-size scratch buffer, so a stream of any size can be checked.
+///
+/// `Ok(Err(offset))` is returned for invalid base64 data, where `offset` is the position of the
+/// first invalid byte, or of the start of the block which cannot be decoded. Errors of the reader
+/// are returned as `Err`.
+#[cfg(feature = "std")]
+#[inline]
+pub fn validate<R: Read>(
+    reader: R,
+    engine: &'static base64::engine::general_purpose::GeneralPurpose,
+) -> Result<Result<(), u64>, io::Error> {
+    Ok(count_decoded(reader, engine)?.map(|_| ()))
+}
+
+/// Decode and validate all the data of a reader like `validate`, and count the plain bytes
+/// instead of keeping them, e.g. to size the buffer of a later decode of the same data. Unlike
+/// `decoded_len`, the reader does not need to be seekable and the data may be of any shape
+/// `engine` accepts, since they are really decoded.
+///
+/// `Ok(Ok(count))` is returned for valid base64 data and `Ok(Err(offset))` for invalid data, as
+/// with `validate`.
+#[cfg(feature = "std")]
+pub fn count_decoded<R: Read>(
+    reader: R,
+    engine: &'static base64::engine::general_purpose::GeneralPurpose,
+) -> Result<Result<u64, u64>, io::Error> {
+    let mut reader = FromBase64Reader::new_with_engine(reader, engine);
+
+    // the engine decodes into it directly, so whole windows are decoded at once and nothing is
+    // copied afterwards
+    let mut scratch = [0u8; 3072];
+
+    let mut count = 0;
+
+    loop {
+        match reader.read(&mut scratch) {
+            Ok(0) => return Ok(Ok(count)),
+            Ok(c) => count += c as u64,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => {
+                return match e.get_ref().and_then(|e| e.downcast_ref::<FromBase64Error>()) {
+                    Some(e) => Ok(Err(e.offset())),
+                    None => Err(e),
+                };
+            }
+        }
+    }
+}
+
+/// Get the exact length of the plain data decoded from the rest of a seekable reader, e.g. for
+/// the `Content-Length` of a decoded HTTP body, without reading the base64 data. It is computed
+/// from the length of the rest of the stream and the `=` padding at its end, and the position of
+/// the reader is restored afterwards.
+///
+/// Only unwrapped base64 data can be measured this way, so an `InvalidData` error is returned if
+/// the stream ends with a line ending or another byte which is not a base64 character, if its
+/// padding does not complete the last block, or if its length cannot be the one of base64 data.
+/// Only the first kilobyte and the last block are inspected, so that data wrapped at the usual
+/// widths are rejected too, but a stray byte in between is not noticed. Use `count_decoded` to
+/// validate the whole data.
+#[cfg(feature = "std")]
+pub fn decoded_len<R: Read + Seek>(reader: &mut R) -> Result<u64, io::Error> {
+    let original_position = reader.stream_position()?;
+
+    let result = measure_decoded_len(reader, original_position);
+
+    reader.seek(SeekFrom::Start(original_position))?;
+
+    result
+}
+
+#[cfg(feature = "std")]
+fn measure_decoded_len<R: Read + Seek>(reader: &mut R, position: u64) -> Result<u64, io::Error> {
+    let invalid_data = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let is_symbol =
+        |b: u8| matches!(b, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'+' | b'/' | b'-' | b'_');
+
+    let length = reader.seek(SeekFrom::End(0))?.saturating_sub(position);
 
+    // the last block tells the padding
+    let mut tail = [0u8; 4];
+
+    let tail_length = length.min(4) as usize;
+
+    reader.seek(SeekFrom::Start(position + length - tail_length as u64))?;
+    reader.read_exact(&mut tail[..tail_length])?;
+
+    let tail = &tail[..tail_length];
+
+    let padding_length = tail.iter().rev().take_while(|&&b| b == b'=').count();
+
+    if padding_length > 2 {
+        return Err(invalid_data("the base64 data end with more than 2 `=`"));
+    }
+
+    if !tail[..(tail_length - padding_length)].iter().all(|&b| is_symbol(b)) {
+        return Err(invalid_data("the base64 data are wrapped or end with an invalid byte"));
+    }
+
+    let unpadded_length = length - padding_length as u64;
+
+    // the start tells whether the data are wrapped, since a line is shorter than a kilobyte
+    let mut head = [0u8; 1024];
+
+    let head_length = unpadded_length.min(head.len() as u64) as usize;
+
+    reader.seek(SeekFrom::Start(position))?;
+    reader.read_exact(&mut head[..head_length])?;
+
+    if !head[..head_length].iter().all(|&b| is_symbol(b)) {
+        return Err(invalid_data("the base64 data are wrapped or have an invalid byte"));
+    }
+
+    if padding_length > 0 && length % 4 != 0 {
+        return Err(invalid_data("the padding of the base64 data does not complete a block"));
+    }
+
+    match unpadded_length % 4 {
+        1 => Err(invalid_data("the length of the base64 data is invalid")),
+        0 => Ok(unpadded_length / 4 * 3),
+        remainder => Ok(unpadded_length / 4 * 3 + remainder - 1),
+    }
+}
+
+/// Encode all data from a reader to base64 data.
+///
+/// The output is read into the returned `String` directly, without an intermediate `Vec`.
+#[cfg(feature = "std")]
+#[inline]
+pub fn encode_to_string<R: Read>(reader: R) -> Result<String, io::Error> {
+    let mut base64 = String::new();
+
+    ToBase64Reader::new(reader).read_to_string(&mut base64)?;
+
+    Ok(base64)
+}
+
+/// Format the live part of a buffer as hex in `Debug` output, rather than the whole buffer.
+pub(crate) struct HexPreview<'a>(pub(crate) &'a [u8]);
+
+impl core::fmt::Debug for HexPreview<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        const MAX_PREVIEW_LENGTH: usize = 32;
+
+        f.write_str("\"")?;
+
+        for b in self.0.iter().take(MAX_PREVIEW_LENGTH) {
+            write!(f, "{:02x}", b)?;
+        }
+
+        if self.0.len() > MAX_PREVIEW_LENGTH {
+            f.write_str("...")?;
+        }
+
+        write!(f, "\" ({} bytes)", self.0.len())
+    }
+}
+
+/// Overwrite `bytes` with zeros in a way which the compiler does not optimize out, so that
+/// sensitive data does not linger in memory.
+#[cfg(feature = "zeroize")]
+#[inline]
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        // SAFETY: `b` is a valid and aligned reference
+        unsafe { core::ptr::write_volatile(b, 0) };
+    }
+
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Get an upper bound of the length of the plain data decoded from `base64_len` bytes of base64
+/// data, for pre-sizing an output buffer.
+///
+/// It is `base64_len / 4 * 3`, with `base64_len` rounded up to a multiple of 4 so that unpadded
+/// data is covered as well. Padding, and whitespaces skipped by `new_ignoring_whitespace` or
+/// `new_lenient`, make the actual length shorter, so this is only an upper bound.
+#[inline]
+pub const fn decoded_len_estimate(base64_len: usize) -> usize {
+    base64_len.div_ceil(4) * 3
+}
+
+/// The default buffer size `N` of the readers and writers, in bytes, e.g. to size a staging
+/// buffer to match.
+pub const BUFFER_SIZE: usize =
+    <generic_array::typenum::U4096 as generic_array::typenum::Unsigned>::USIZE;
+
+/// Get the smallest buffer size `N` which the readers and writers accept: one 4-byte base64
+/// block, as required by their `IsGreaterOrEqual<U4>` bound at compile time. The buffer is
+/// compacted whenever fewer than 4 bytes are left after its offset, so any size from this one
+/// up works, though small ones make many small reads.
+#[inline]
+pub const fn min_buffer_size() -> usize {
+    4
+}
+
+/// Get the smallest buffer size with which a base64 reader decodes `decode_chunk` bytes per read
+/// from the inner reader: the base64 length of `decode_chunk` bytes, rounded up to whole blocks,
+/// plus 4 bytes so that it fits next to an incomplete block left over from the previous read.
+///
+/// The buffer size is a `typenum` type, so pick one at least this large. It can be checked at
+/// compile time:
+///
+/// ```rust
+/// use base64_stream::generic_array::typenum::{Unsigned, U16384};
+///
+/// const _: () = assert!(U16384::USIZE >= base64_stream::recommended_buffer_size(8192));
+/// ```
+#[inline]
+pub const fn recommended_buffer_size(decode_chunk: usize) -> usize {
+    decode_chunk.div_ceil(3) * 4 + 4
+}
+
+/// Get the exact length of the base64 data encoded from `plain_len` bytes, with or without `=`
+/// padding. Line endings inserted by `ToBase64Reader::new_wrapped` are not counted.
+#[inline]
+pub const fn encoded_len(plain_len: usize, padded: bool) -> usize {
+    let complete_length = plain_len / 3 * 4;
+
+    match plain_len % 3 {
+        0 => complete_length,
+        _ if padded => complete_length + 4,
+        remainder => complete_length + remainder + 1,
+    }
+}
+
+/// Check whether `data` is base64 data which `engine` can decode, without decoding it, e.g. to
+/// reject user input early. Every byte must be a character of the alphabet of `engine`, except
+/// for at most 2 trailing `=`, and the last block must satisfy the padding mode and the canonical
+/// check of `engine`. Whitespaces are not allowed.
+pub fn is_valid_base64(
+    data: &[u8],
+    engine: &base64::engine::general_purpose::GeneralPurpose,
+) -> bool {
+    use base64::Engine;
+
+    let padding_length = data.iter().rev().take(3).take_while(|&&b| b == b'=').count();
+
+    if padding_length > 2 {
+        return false;
+    }
+
+    let body = &data[..(data.len() - padding_length)];
+
+    if !body.iter().all(|&b| is_symbol(engine, b)) {
+        return false;
+    }
+
+    // the engine judges the padding and the trailing bits of the last, possibly short, block
+    let last_block = &data[(data.len().saturating_sub(1) & !3)..];
+
+    engine.decode_slice(last_block, &mut [0u8; 3]).is_ok()
+}
+
+/// Whether `b` is a character of the alphabet of `engine`. A block of 4 equal characters has no
+/// padding or trailing bits to reject, so only the alphabet decides whether it decodes.
+#[inline]
+pub(crate) fn is_symbol(engine: &base64::engine::general_purpose::GeneralPurpose, b: u8) -> bool {
+    use base64::Engine;
+
+    b != b'=' && engine.decode_slice([b; 4], &mut [0u8; 3]).is_ok()
+}
 
 pub fn to_decode_error(src: base64::DecodeSliceError) -> base64::DecodeError
 { 
@@ -150,6 +569,7 @@ pub fn to_decode_error(src: base64::DecodeSliceError) -> base64::DecodeError
     }
 }
 
+#[cfg(feature = "std")]
 pub fn to_io_error(err: base64::EncodeSliceError) -> std::io::Error
 { 
     std::io::Error::new(std::io::ErrorKind::Other, err)
@@ -0,0 +1,188 @@
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::Path;
+use std::thread;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::{DecodeError, Engine};
+
+use crate::{DecoderError, FromBase64Error};
+
+/// The size of the base64 data decoded by each thread at a time. It is a multiple of 4.
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Decode the standard base64 data in the file at `path` with a thread per CPU core, and write
+/// the plain data to `out` in order. The number of decoded bytes is returned.
+///
+/// The file is read in batches of a chunk per thread, which are split at 4-byte boundaries and
+/// decoded concurrently. The threads are plain scoped `std::thread`s spawned for every batch, not a
+/// thread pool, so no runtime is needed but each batch pays for spawning them. Padding is only
+/// accepted at the end of the last chunk. The split only lines up with the base64 blocks if the
+/// data is not wrapped, so whitespaces are rejected as invalid data, except at the end of the file.
+/// Wrapped data has to be stripped first, or be decoded by
+/// `FromBase64Reader::new_ignoring_whitespace`.
+pub fn decode_file_parallel<P: AsRef<Path>, W: Write>(
+    path: P,
+    mut out: W,
+) -> Result<u64, io::Error> {
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    let mut file = File::open(path)?;
+
+    let file_length = file.metadata()?.len();
+
+    let mut batch = vec![0u8; CHUNK_SIZE * threads];
+    let mut decoded = vec![Vec::new(); threads];
+
+    let mut offset = 0;
+    let mut decoded_count = 0;
+
+    loop {
+        let length = read_full(&mut file, &mut batch)?;
+
+        let mut data = &batch[..length];
+
+        let mut at_end = length < batch.len() || offset + length as u64 >= file_length;
+
+        let trimmed = data.trim_ascii_end();
+
+        // whitespaces at the end of a full batch are fine if the rest of the file is blank too,
+        // otherwise they are left in to be rejected by the engine
+        if trimmed.len() < length && (at_end || rest_is_blank(&mut file)?) {
+            data = trimmed;
+            at_end = true;
+        }
+
+        decode_batch(data, offset, at_end, &mut decoded)?;
+
+        for chunk in decoded.iter() {
+            out.write_all(chunk)?;
+
+            decoded_count += chunk.len() as u64;
+        }
+
+        if at_end {
+            break;
+        }
+
+        offset += length as u64;
+    }
+
+    out.flush()?;
+
+    Ok(decoded_count)
+}
+
+/// Decode `data`, which starts at `offset` in the file, into one buffer of `decoded` per thread.
+/// Its last chunk may only be padded if it ends the file, as told by `at_end`.
+fn decode_batch(
+    data: &[u8],
+    offset: u64,
+    at_end: bool,
+    decoded: &mut [Vec<u8>],
+) -> Result<(), io::Error> {
+    let chunk_size = data.len().div_ceil(decoded.len()).next_multiple_of(4).max(4);
+
+    for buf in decoded.iter_mut() {
+        buf.clear();
+    }
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = data
+            .chunks(chunk_size)
+            .zip(decoded.iter_mut())
+            .enumerate()
+            .map(|(i, (chunk, buf))| {
+                let chunk_offset = offset + (i * chunk_size) as u64;
+
+                let last = at_end && (i + 1) * chunk_size >= data.len();
+
+                scope.spawn(move || decode_chunk(chunk, chunk_offset, last, buf))
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().expect("a decoding thread panicked")?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Decode `chunk`, which starts at `chunk_offset` in the file, into `buf`. Only the `last` chunk
+/// may end with padding, since the engine cannot tell that more data follows the others.
+fn decode_chunk(
+    chunk: &[u8],
+    chunk_offset: u64,
+    last: bool,
+    buf: &mut Vec<u8>,
+) -> Result<(), io::Error> {
+    if !last {
+        let tail = chunk.len().saturating_sub(4);
+
+        if let Some(index) = chunk[tail..].iter().position(|&b| b == b'=') {
+            let index = tail + index;
+
+            return Err(chunk_error(chunk, chunk_offset, DecodeError::InvalidByte(index, b'=')));
+        }
+    }
+
+    buf.resize(crate::decoded_len_estimate(chunk.len()), 0);
+
+    match STANDARD.decode_slice(chunk, buf) {
+        Ok(c) => {
+            buf.truncate(c);
+
+            Ok(())
+        }
+        Err(err) => Err(chunk_error(chunk, chunk_offset, crate::to_decode_error(err))),
+    }
+}
+
+/// Attach the file offset and the failed block to an error of the engine for `chunk`.
+fn chunk_error(chunk: &[u8], chunk_offset: u64, error: DecodeError) -> io::Error {
+    let index = match error {
+        DecodeError::InvalidByte(index, _) | DecodeError::InvalidLastSymbol(index, _) => index,
+        _ => 0,
+    };
+
+    let window = &chunk[(index & !0b11).min(chunk.len())..];
+
+    DecoderError::Decode(
+        FromBase64Error::new(chunk_offset + index as u64, error).with_window(window),
+    )
+    .into()
+}
+
+/// Read until `buf` is full or the end of the file is reached.
+fn read_full(file: &mut File, buf: &mut [u8]) -> Result<usize, io::Error> {
+    let mut length = 0;
+
+    while length < buf.len() {
+        match file.read(&mut buf[length..]) {
+            Ok(0) => break,
+            Ok(c) => length += c,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(length)
+}
+
+/// Whether the rest of the file contains only whitespaces.
+fn rest_is_blank(file: &mut File) -> Result<bool, io::Error> {
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let length = read_full(file, &mut buf)?;
+
+        if !buf[..length].iter().all(u8::is_ascii_whitespace) {
+            return Ok(false);
+        }
+
+        if length < buf.len() {
+            return Ok(true);
+        }
+    }
+}
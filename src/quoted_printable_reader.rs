@@ -0,0 +1,269 @@
+use std::io::{self, ErrorKind, Read};
+
+/// What the bytes after a `=` have turned out to be so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    /// After `=`.
+    Equals,
+    /// After `=` and spaces or tabs, which rule out an escape.
+    EqualsSpaced,
+    /// After `=` and the first hex digit of an escape.
+    Escape(u8),
+    /// After `=` and `\r`.
+    EqualsCr,
+    /// After `=` and a line break where the `=` may also be padding, skipping whitespaces until
+    /// the next byte tells which one it is.
+    PaddingOrBreak,
+    /// As `PaddingOrBreak`, and a `=` has followed, which is an escape if a hex digit comes next.
+    PaddingOrBreakEquals,
+}
+
+/// Read base64 data wrapped in quoted-printable, e.g. from an email pipeline which encodes twice,
+/// and return the base64 data. Soft line breaks, `=\r\n` or `=\n`, are removed, and `=XX`
+/// escapes, such as `=3D` for the padding, are unescaped. Other bytes, including hard line
+/// breaks, are passed through, so the base64 data should be decoded ignoring whitespaces, as
+/// `FromBase64Reader::new_quoted_printable` does.
+///
+/// A pipeline may also leave the padding unescaped, so a `=` which is not followed by a line
+/// break or by two hex digits is returned as it is. A `=` and a line break is ambiguous then: it
+/// is taken as padding only where padding may stand, i.e. after 2 or 3 characters of a block,
+/// and if nothing but whitespaces or more `=` padding follows it up to the end of the stream.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct QuotedPrintableReader<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    #[educe(Debug(ignore))]
+    input: [u8; 1024],
+    input_offset: usize,
+    input_length: usize,
+    eof: bool,
+    state: State,
+    /// The number of base64 characters returned in the current block.
+    position: u8,
+    /// A byte which did not fit in the read buffer, returned by the next read.
+    held: Option<u8>,
+}
+
+impl<R: Read> QuotedPrintableReader<R> {
+    #[inline]
+    pub fn new(reader: R) -> QuotedPrintableReader<R> {
+        QuotedPrintableReader {
+            inner: reader,
+            input: [0; 1024],
+            input_offset: 0,
+            input_length: 0,
+            eof: false,
+            state: State::Data,
+            position: 0,
+            held: None,
+        }
+    }
+
+    /// Unwrap this reader, returning the inner reader. Data which has been read from it but not
+    /// returned yet is discarded.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Handle the next input byte, or the end of the stream for `None`, and return whether it is
+    /// consumed. A byte which is not consumed is handled again in the new state.
+    fn step(&mut self, b: Option<u8>, out: &mut Output) -> bool {
+        match (self.state, b) {
+            (State::Data, None) => true,
+            (State::Data, Some(b'=')) => {
+                self.state = State::Equals;
+
+                true
+            }
+            (State::Data, Some(b)) => {
+                self.emit(b, out);
+
+                true
+            }
+            (State::Equals | State::EqualsSpaced, Some(b' ' | b'\t')) => {
+                self.state = State::EqualsSpaced;
+
+                true
+            }
+            (State::Equals | State::EqualsSpaced, Some(b'\r')) => {
+                self.state = State::EqualsCr;
+
+                true
+            }
+            (State::Equals | State::EqualsSpaced | State::EqualsCr, Some(b'\n')) => {
+                self.line_break();
+
+                true
+            }
+            (State::Equals, Some(b)) if b.is_ascii_hexdigit() => {
+                self.state = State::Escape(b);
+
+                true
+            }
+            (State::Escape(high), Some(low)) if low.is_ascii_hexdigit() => {
+                self.emit((hex_value(high) << 4) | hex_value(low), out);
+
+                self.state = State::Data;
+
+                true
+            }
+            (State::Escape(high), _) => {
+                self.emit(b'=', out);
+                self.emit(high, out);
+
+                self.state = State::Data;
+
+                false
+            }
+            // not a soft line break nor an escape, but an unescaped `=`
+            (State::Equals | State::EqualsSpaced | State::EqualsCr, _) => {
+                self.emit(b'=', out);
+
+                self.state = State::Data;
+
+                false
+            }
+            (State::PaddingOrBreak, Some(b)) if b.is_ascii_whitespace() => true,
+            (State::PaddingOrBreak, Some(b'=')) => {
+                self.state = State::PaddingOrBreakEquals;
+
+                true
+            }
+            (State::PaddingOrBreak, Some(_)) => {
+                // more data follow, so it was a soft line break
+                self.state = State::Data;
+
+                false
+            }
+            (State::PaddingOrBreakEquals, Some(b)) if b.is_ascii_hexdigit() => {
+                // an escape follows the soft line break, e.g. `=3D` for the padding
+                self.state = State::Escape(b);
+
+                true
+            }
+            (State::PaddingOrBreak | State::PaddingOrBreakEquals, _) => {
+                // only padding or the end of the stream follows, so it was padding
+                self.emit(b'=', out);
+
+                self.state = if self.state == State::PaddingOrBreakEquals {
+                    State::Equals
+                } else {
+                    State::Data
+                };
+
+                false
+            }
+        }
+    }
+
+    /// Handle a line break after `=`, which is a soft line break unless the `=` may be padding.
+    #[inline]
+    fn line_break(&mut self) {
+        self.state = if self.position >= 2 {
+            State::PaddingOrBreak
+        } else {
+            State::Data
+        };
+    }
+
+    #[inline]
+    fn emit(&mut self, b: u8, out: &mut Output) {
+        if !b.is_ascii_whitespace() {
+            self.position = (self.position + 1) & 0b11;
+        }
+
+        if out.length < out.capacity {
+            out.pending[out.length] = b;
+            out.length += 1;
+        } else {
+            debug_assert!(self.held.is_none());
+
+            self.held = Some(b);
+        }
+    }
+}
+
+/// The bytes returned by a step, at most 2, and how many of them fit in the read buffer.
+struct Output {
+    pending: [u8; 2],
+    length: usize,
+    capacity: usize,
+}
+
+#[inline]
+fn hex_value(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'A'..=b'F' => b - b'A' + 10,
+        _ => b - b'a' + 10,
+    }
+}
+
+impl<R: Read> Read for QuotedPrintableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut length = 0;
+
+        if let Some(b) = self.held.take() {
+            buf[0] = b;
+
+            length = 1;
+        }
+
+        while length < buf.len() && self.held.is_none() {
+            let b = if self.input_offset < self.input_length {
+                Some(self.input[self.input_offset])
+            } else if self.eof {
+                if self.state == State::Data {
+                    break;
+                }
+
+                None
+            } else if length > 0 {
+                // return what is there rather than wait for more data
+                break;
+            } else {
+                match self.inner.read(&mut self.input) {
+                    Ok(0) => self.eof = true,
+                    Ok(c) => {
+                        self.input_offset = 0;
+                        self.input_length = c;
+                    }
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+
+                continue;
+            };
+
+            let mut out = Output {
+                pending: [0; 2],
+                length: 0,
+                capacity: (buf.len() - length).min(2),
+            };
+
+            if self.step(b, &mut out) && b.is_some() {
+                self.input_offset += 1;
+            }
+
+            buf[length..(length + out.length)].copy_from_slice(&out.pending[..out.length]);
+
+            length += out.length;
+        }
+
+        Ok(length)
+    }
+}
+
+impl<R: Read> From<R> for QuotedPrintableReader<R> {
+    #[inline]
+    fn from(reader: R) -> Self {
+        QuotedPrintableReader::new(reader)
+    }
+}
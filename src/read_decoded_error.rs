@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use base64::DecodeError;
+
+use crate::{DecoderError, FromBase64Error};
+
+/// An error returned by `FromBase64Reader::read_decoded`, which keeps the errors of the base64
+/// data apart from the errors of the inner reader.
+#[derive(Debug)]
+pub enum ReadDecodedError {
+    /// The inner reader failed.
+    Io(io::Error),
+    /// The base64 stream cannot be decoded.
+    Decode(DecoderError),
+}
+
+impl ReadDecodedError {
+    /// Get the underlying decode error if the base64 data is invalid, e.g. to tell
+    /// `InvalidLength` caused by truncated data from `InvalidByte` caused by corrupted data.
+    #[inline]
+    pub fn decode_error(&self) -> Option<&DecodeError> {
+        match self {
+            ReadDecodedError::Decode(DecoderError::Decode(err)) => Some(err.error()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadDecodedError {
+    /// Take back the `DecoderError` or `FromBase64Error` which the reader has wrapped.
+    fn from(err: io::Error) -> Self {
+        let kind = err.kind();
+
+        match err.get_ref() {
+            Some(inner) if inner.is::<DecoderError>() || inner.is::<FromBase64Error>() => (),
+            _ => return ReadDecodedError::Io(err),
+        }
+
+        let inner = err.into_inner().unwrap();
+
+        let inner = match inner.downcast::<FromBase64Error>() {
+            Ok(inner) => return ReadDecodedError::Decode(DecoderError::Decode(*inner)),
+            Err(inner) => inner,
+        };
+
+        match inner.downcast::<DecoderError>() {
+            Ok(inner) => ReadDecodedError::Decode(*inner),
+            Err(inner) => ReadDecodedError::Io(io::Error::new(kind, inner)),
+        }
+    }
+}
+
+impl Display for ReadDecodedError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ReadDecodedError::Io(err) => Display::fmt(err, f),
+            ReadDecodedError::Decode(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for ReadDecodedError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadDecodedError::Io(err) => Some(err),
+            ReadDecodedError::Decode(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::io::{self, ErrorKind, Read};
+
+/// Read unpadded base64 data and append the `=` padding which the last block lacks, without
+/// decoding anything. Whitespaces are passed through and not counted as base64 characters, so
+/// the padding follows any trailing line ending.
+///
+/// Data which is already padded is left as it is. A last block of a single character cannot be
+/// padded, so an `InvalidData` error is returned for it.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct RepadReader<R: Read> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    /// The number of base64 characters in the current block.
+    position: u8,
+    /// The number of `=` left to be returned, once the inner reader has reached EOF.
+    padding: Option<u8>,
+}
+
+impl<R: Read> RepadReader<R> {
+    #[inline]
+    pub fn new(reader: R) -> RepadReader<R> {
+        RepadReader {
+            inner: reader,
+            position: 0,
+            padding: None,
+        }
+    }
+
+    /// Unwrap this reader, returning the inner reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for RepadReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(padding) = self.padding {
+            let drain_length = buf.len().min(padding as usize);
+
+            buf[..drain_length].fill(b'=');
+
+            self.padding = Some(padding - drain_length as u8);
+
+            return Ok(drain_length);
+        }
+
+        let c = loop {
+            match self.inner.read(buf) {
+                Ok(c) => break c,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+
+        if c == 0 {
+            let padding = match self.position {
+                0 => 0,
+                1 => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "a last block of a single base64 character cannot be padded",
+                    ))
+                }
+                position => 4 - position,
+            };
+
+            self.padding = Some(padding);
+
+            return self.read(buf);
+        }
+
+        for &b in &buf[..c] {
+            if !b.is_ascii_whitespace() {
+                self.position = (self.position + 1) & 0b11;
+            }
+        }
+
+        Ok(c)
+    }
+}
+
+impl<R: Read> From<R> for RepadReader<R> {
+    #[inline]
+    fn from(reader: R) -> Self {
+        RepadReader::new(reader)
+    }
+}
@@ -1,4 +1,4 @@
-use std::intrinsics::{copy, copy_nonoverlapping};
+use std::fmt::{self, Debug, Formatter};
 use std::io::{self, ErrorKind, Read};
 
 use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
@@ -8,20 +8,31 @@ use base64::{self,
     Engine,
 };
 
+use crate::HexPreview;
+
 /// Read any data and encode them to base64 data.
-#[derive(Educe)]
-#[educe(Debug)]
 pub struct ToBase64Reader<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096>
 {
-    #[educe(Debug(ignore))]
     inner: R,
     buf: GenericArray<u8, N>,
     buf_length: usize,
     buf_offset: usize,
     temp: [u8; 3],
     temp_length: usize,
-    #[educe(Debug(ignore))]
     engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    line_width: usize,
+    line_ending: &'static [u8],
+    line_ending_offset: usize,
+    /// Whether the last line is ended too, rather than only the lines followed by more data.
+    end_last_line: bool,
+    column: usize,
+    final_group: Option<u8>,
+    /// The number of plain bytes encoded so far, reported to `on_progress`.
+    encoded_count: u64,
+    on_progress: Option<Box<dyn FnMut(u64) + Send>>,
+    /// Returned before the base64 data, e.g. `data:text/plain;base64,`.
+    prefix: String,
+    prefix_offset: usize,
 }
 
 impl<R: Read> ToBase64Reader<R> {
@@ -29,6 +40,60 @@ impl<R: Read> ToBase64Reader<R> {
     pub fn new(reader: R) -> ToBase64Reader<R> {
         Self::new2(reader, &base64::engine::general_purpose::STANDARD)
     }
+
+    /// Create a reader which encodes with the given engine, e.g. `URL_SAFE` or `URL_SAFE_NO_PAD`
+    /// for base64url data. If the engine does not pad, the last block is not padded with `=`.
+    #[inline]
+    pub fn new_with_engine(
+        reader: R,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> ToBase64Reader<R> {
+        Self::new2(reader, engine)
+    }
+
+    /// Create a reader which ends every `width` base64 characters with `line_ending`, e.g. 76 and
+    /// `b"\r\n"` for MIME bodies or 64 and `b"\n"` for PEM files. The last line is ended too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    #[inline]
+    pub fn new_wrapped(reader: R, width: usize, line_ending: &'static [u8]) -> ToBase64Reader<R> {
+        assert!(width > 0, "the line width must be greater than zero");
+
+        let mut reader = Self::new(reader);
+
+        reader.line_width = width;
+        reader.line_ending = line_ending;
+        reader.line_ending_offset = line_ending.len();
+        reader.end_last_line = true;
+
+        reader
+    }
+
+    /// Create a reader for RFC 2045 MIME bodies, which encodes with the standard alphabet and
+    /// padding, and ends every 76 characters with `\r\n`. Unlike `new_wrapped`, the last line is
+    /// not ended; use `new_wrapped(reader, 76, b"\r\n")` to end it too.
+    #[inline]
+    pub fn new_mime(reader: R) -> ToBase64Reader<R> {
+        let mut reader = Self::new_wrapped(reader, 76, b"\r\n");
+
+        reader.end_last_line = false;
+
+        reader
+    }
+
+    /// Create a reader which returns `prefix` before the base64 data, e.g.
+    /// `data:image/png;base64,` to produce a data URI, which `FromBase64Reader::new_data_uri`
+    /// decodes back.
+    #[inline]
+    pub fn with_prefix(reader: R, prefix: String) -> ToBase64Reader<R> {
+        let mut reader = Self::new(reader);
+
+        reader.prefix = prefix;
+
+        reader
+    }
 }
 
 impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64Reader<R, N> {
@@ -42,29 +107,57 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64
             temp: [0; 3],
             temp_length: 0,
             engine,
+            line_width: 0,
+            line_ending: b"",
+            line_ending_offset: 0,
+            end_last_line: false,
+            column: 0,
+            final_group: None,
+            encoded_count: 0,
+            on_progress: None,
+            prefix: String::new(),
+            prefix_offset: 0,
         }
     }
 }
 
 impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64Reader<R, N> {
+    /// Get the number of plain bytes in the last group of 3, i.e. the length of the data modulo 3,
+    /// which a receiver of unpadded base64 data may need to tell the exact length.
+    ///
+    /// It is `None` until the inner reader has reached EOF and the last group has been encoded
+    /// and returned by `read`, so it should be called after `read` has returned `0`.
+    #[inline]
+    pub fn final_group_bytes(&self) -> Option<u8> {
+        if self.buf_length == 0 && self.temp_length == 0 {
+            self.final_group
+        } else {
+            None
+        }
+    }
+
+    /// Call `f` with the number of plain bytes encoded so far each time `read` returns encoded
+    /// data, e.g. to report the progress of an upload.
+    #[inline]
+    pub fn on_progress(mut self, f: impl FnMut(u64) + Send + 'static) -> Self {
+        self.on_progress = Some(Box::new(f));
+
+        self
+    }
+
     fn buf_left_shift(&mut self, distance: usize) {
         debug_assert!(self.buf_length >= distance);
 
+        self.encoded_count += distance as u64;
+
         self.buf_offset += distance;
+        self.buf_length -= distance;
 
         if self.buf_offset >= N::USIZE - 4 {
-            unsafe {
-                copy(
-                    self.buf.as_ptr().add(self.buf_offset),
-                    self.buf.as_mut_ptr(),
-                    self.buf_length,
-                );
-            }
+            self.buf.copy_within(self.buf_offset..(self.buf_offset + self.buf_length), 0);
 
             self.buf_offset = 0;
         }
-
-        self.buf_length -= distance;
     }
 
     #[inline]
@@ -74,19 +167,11 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64
 
         let drain_length = buf.len().min(self.temp_length);
 
-        unsafe {
-            copy_nonoverlapping(self.temp.as_ptr(), buf.as_mut_ptr(), drain_length);
-        }
+        buf[..drain_length].copy_from_slice(&self.temp[..drain_length]);
 
-        self.temp_length -= drain_length;
+        self.temp.copy_within(drain_length..self.temp_length, 0);
 
-        unsafe {
-            copy(
-                self.temp.as_ptr().add(self.temp_length),
-                self.temp.as_mut_ptr(),
-                self.temp_length,
-            );
-        }
+        self.temp_length -= drain_length;
 
         &mut buf[drain_length..]
     }
@@ -116,27 +201,17 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64
         let buf_length = buf.len();
 
         if buf_length >= encode_length {
-            unsafe {
-                copy_nonoverlapping(b.as_ptr(), buf.as_mut_ptr(), encode_length);
-            }
+            buf[..encode_length].copy_from_slice(&b[..encode_length]);
 
             buf = &mut buf[encode_length..];
         } else {
-            unsafe {
-                copy_nonoverlapping(b.as_ptr(), buf.as_mut_ptr(), buf_length);
-            }
+            buf.copy_from_slice(&b[..buf_length]);
 
             buf = &mut buf[buf_length..];
 
             self.temp_length = encode_length - buf_length;
 
-            unsafe {
-                copy_nonoverlapping(
-                    b.as_ptr().add(buf_length),
-                    self.temp.as_mut_ptr(),
-                    self.temp_length,
-                );
-            }
+            self.temp[..self.temp_length].copy_from_slice(&b[buf_length..encode_length]);
         }
 
         buf
@@ -201,17 +276,18 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64
             buf
         }
     }
-}
 
-impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
-    for ToBase64Reader<R, N>
-{
-    fn read(&mut self, mut buf: &mut [u8]) -> Result<usize, io::Error> {
+    fn encode_into(&mut self, mut buf: &mut [u8]) -> Result<usize, io::Error> {
         let original_buf_length = buf.len();
 
         while self.buf_length < 3 {
             match self.inner.read(&mut self.buf[(self.buf_offset + self.buf_length)..]) {
                 Ok(0) => {
+                    // whole groups are always encoded first, so only the last group is left
+                    if self.final_group.is_none() {
+                        self.final_group = Some(self.buf_length as u8);
+                    }
+
                     buf = self.drain_end(buf);
 
                     return Ok(original_buf_length - buf.len());
@@ -226,6 +302,146 @@ impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
 
         Ok(original_buf_length - buf.len())
     }
+
+    /// Whether any encoded data are left to be returned, reading from the inner reader if
+    /// nothing is buffered.
+    fn has_more(&mut self) -> Result<bool, io::Error> {
+        while self.buf_length == 0 && self.temp_length == 0 {
+            if self.final_group.is_some() {
+                return Ok(false);
+            }
+
+            match self.inner.read(&mut self.buf[self.buf_offset..]) {
+                Ok(0) => {
+                    self.final_group = Some(0);
+
+                    return Ok(false);
+                }
+                Ok(c) => self.buf_length += c,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Encode data into `buf` and insert a line ending after every `line_width` characters.
+    fn encode_wrapped_into(&mut self, mut buf: &mut [u8]) -> Result<usize, io::Error> {
+        let original_buf_length = buf.len();
+
+        loop {
+            if self.line_ending_offset < self.line_ending.len() {
+                let line_ending = &self.line_ending[self.line_ending_offset..];
+
+                let drain_length = line_ending.len().min(buf.len());
+
+                buf[..drain_length].copy_from_slice(&line_ending[..drain_length]);
+
+                buf = &mut buf[drain_length..];
+
+                self.line_ending_offset += drain_length;
+            }
+
+            if buf.is_empty() {
+                break;
+            }
+
+            if self.column == self.line_width {
+                // a full line is only ended once more data are known to follow it
+                if !self.end_last_line && !self.has_more()? {
+                    break;
+                }
+
+                self.column = 0;
+                self.line_ending_offset = 0;
+
+                continue;
+            }
+
+            let max_length = (self.line_width - self.column).min(buf.len());
+
+            let encode_length = self.encode_into(&mut buf[..max_length])?;
+
+            if encode_length == 0 {
+                if self.column == 0 || !self.end_last_line {
+                    break;
+                }
+
+                // end the last line
+                self.column = 0;
+                self.line_ending_offset = 0;
+
+                continue;
+            }
+
+            buf = &mut buf[encode_length..];
+
+            self.column += encode_length;
+
+            if self.column < self.line_width && encode_length < max_length {
+                break;
+            }
+        }
+
+        Ok(original_buf_length - buf.len())
+    }
+}
+
+/// Only the buffered plain data and the encoded bytes left behind are shown, not the whole
+/// buffers.
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Debug
+    for ToBase64Reader<R, N>
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let buf = &self.buf[self.buf_offset..(self.buf_offset + self.buf_length)];
+
+        f.debug_struct("ToBase64Reader")
+            .field("buf", &HexPreview(buf))
+            .field("buf_offset", &self.buf_offset)
+            .field("buf_length", &self.buf_length)
+            .field("temp", &HexPreview(&self.temp[..self.temp_length]))
+            .field("temp_length", &self.temp_length)
+            .field("line_width", &self.line_width)
+            .field("end_last_line", &self.end_last_line)
+            .field("column", &self.column)
+            .field("final_group", &self.final_group)
+            .field("encoded_count", &self.encoded_count)
+            .finish()
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
+    for ToBase64Reader<R, N>
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if self.prefix_offset < self.prefix.len() {
+            let prefix = &self.prefix.as_bytes()[self.prefix_offset..];
+
+            let drain_length = prefix.len().min(buf.len());
+
+            buf[..drain_length].copy_from_slice(&prefix[..drain_length]);
+
+            self.prefix_offset += drain_length;
+
+            return Ok(drain_length);
+        }
+
+        let c = if self.line_width > 0 {
+            self.encode_wrapped_into(buf)?
+        } else {
+            self.encode_into(buf)?
+        };
+
+        if c > 0 {
+            if let Some(on_progress) = self.on_progress.as_mut() {
+                on_progress(self.encoded_count);
+            }
+        }
+
+        Ok(c)
+    }
 }
 
 impl<R: Read> From<R> for ToBase64Reader<R> {
@@ -234,3 +450,14 @@ impl<R: Read> From<R> for ToBase64Reader<R> {
         ToBase64Reader::new(reader)
     }
 }
+
+/// Create a reader over the default inner reader, e.g. an empty `Cursor`, with the options of
+/// `new` and the buffer size `N`.
+impl<R: Read + Default, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Default
+    for ToBase64Reader<R, N>
+{
+    #[inline]
+    fn default() -> Self {
+        ToBase64Reader::new2(R::default(), &base64::engine::general_purpose::STANDARD)
+    }
+}
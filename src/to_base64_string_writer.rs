@@ -0,0 +1,96 @@
+use std::io::{self, Write};
+use std::str;
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::ArrayLength;
+
+use crate::encoder::Encoder;
+
+/// Write any data and encode them to base64 data in a `String`, the push counterpart of
+/// `encode_to_string`.
+///
+/// The last incomplete block is only encoded by `flush` or `into_string`, so data can be written
+/// in pieces of any size.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct ToBase64StringWriter<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096> {
+    base64: String,
+    encoder: Encoder<N>,
+}
+
+impl ToBase64StringWriter {
+    #[inline]
+    pub fn new() -> ToBase64StringWriter {
+        Self::new2(&base64::engine::general_purpose::STANDARD)
+    }
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64StringWriter<N> {
+    #[inline]
+    pub fn new2(
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> ToBase64StringWriter<N> {
+        ToBase64StringWriter {
+            base64: String::new(),
+            encoder: Encoder::new(engine),
+        }
+    }
+
+    /// Get the base64 data encoded so far, without the last incomplete block.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.base64
+    }
+
+    /// Encode the last incomplete block and return the base64 data.
+    #[inline]
+    pub fn into_string(mut self) -> String {
+        self.encoder.finish().expect("encoding into a buffer never fails");
+
+        self.push_pending();
+
+        self.base64
+    }
+
+    fn push_pending(&mut self) {
+        let pending = self.encoder.pending();
+
+        let pending_length = pending.len();
+
+        self.base64.push_str(str::from_utf8(pending).expect("base64 data is ASCII"));
+
+        self.encoder.consume(pending_length);
+    }
+}
+
+impl<N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write for ToBase64StringWriter<N> {
+    fn write(&mut self, mut buf: &[u8]) -> Result<usize, io::Error> {
+        let original_buf_length = buf.len();
+
+        while !buf.is_empty() {
+            let c = self.encoder.encode(buf)?;
+
+            buf = &buf[c..];
+
+            self.push_pending();
+        }
+
+        Ok(original_buf_length)
+    }
+
+    /// Encode the last incomplete block. Only call it once all data has been written.
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.encoder.finish()?;
+
+        self.push_pending();
+
+        Ok(())
+    }
+}
+
+impl Default for ToBase64StringWriter {
+    #[inline]
+    fn default() -> Self {
+        ToBase64StringWriter::new()
+    }
+}
@@ -1,28 +1,32 @@
-use std::intrinsics::copy_nonoverlapping;
 use std::io::{self, Write};
 
 use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
-use generic_array::{ArrayLength, GenericArray};
+use generic_array::ArrayLength;
 
-use base64::{self,
-    Engine,
-};
+use crate::encoder::Encoder;
+
+const TAKEN: &str = "the inner writer is only taken out by `finish`";
 
 /// Write base64 data and encode them to plain data.
+///
+/// By default, the encoded data of every `write` call is written to the inner writer right away.
+/// Use `with_flush_threshold` to collect it and write it in fewer, larger chunks instead.
 #[derive(Educe)]
 #[educe(Debug)]
 pub struct ToBase64Writer<
     W: Write,
     N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
 > {
+    /// Only taken out by `finish`.
     #[educe(Debug(ignore))]
-    inner: W,
-    buf: [u8; 3],
-    buf_length: usize,
-    temp: GenericArray<u8, N>,
-    #[educe(Debug(ignore))]
-    engine: &'static base64::engine::general_purpose::GeneralPurpose,
-//    engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    inner: Option<W>,
+    encoder: Encoder<N>,
+    /// The encoded data collected until it reaches `flush_threshold` bytes.
+    output: Vec<u8>,
+    flush_threshold: usize,
+    /// Whether `finish` has been called, so that `drop` does not write again what a failed
+    /// `finish` may already have written in part.
+    finished: bool,
 }
 
 impl<W: Write> ToBase64Writer<W> {
@@ -30,107 +34,124 @@ impl<W: Write> ToBase64Writer<W> {
     pub fn new(writer: W) -> ToBase64Writer<W> {
         Self::new2(writer, &base64::engine::general_purpose::STANDARD)
     }
+
+    /// Create a writer which collects the encoded data and writes it to `writer` only once at
+    /// least `flush_threshold` bytes have been collected, e.g. to avoid a system call per small
+    /// write to a `File` or a socket. `0` writes every chunk right away, like `new`.
+    ///
+    /// `flush` and `finish` write out everything which has been collected.
+    #[inline]
+    pub fn with_flush_threshold(writer: W, flush_threshold: usize) -> ToBase64Writer<W> {
+        let mut writer = Self::new(writer);
+
+        writer.output = Vec::with_capacity(flush_threshold);
+        writer.flush_threshold = flush_threshold;
+
+        writer
+    }
 }
 
 impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64Writer<W, N> {
     #[inline]
     pub fn new2(writer: W, engine: &'static base64::engine::general_purpose::GeneralPurpose) -> ToBase64Writer<W, N> {
         ToBase64Writer {
-            inner: writer,
-            buf: [0; 3],
-            buf_length: 0,
-            temp: GenericArray::default(),
-            engine,
+            inner: Some(writer),
+            encoder: Encoder::new(engine),
+            output: Vec::new(),
+            flush_threshold: 0,
+            finished: false,
         }
     }
 }
 
 impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64Writer<W, N> {
-    fn drain_block(&mut self) -> Result<(), io::Error> {
-        debug_assert!(self.buf_length > 0);
+    /// Encode the last incomplete block, flush the inner writer and return it.
+    ///
+    /// Nothing can be written after calling this method, because the writer is consumed. Unlike
+    /// dropping the writer, any error is returned.
+    pub fn finish(mut self) -> Result<W, io::Error> {
+        self.finished = true;
 
-        let encode_length = self.engine.encode_slice(
-            self.buf[..self.buf_length].as_ref(),
-            &mut self.temp,
-        ).map_err(|err| super::to_io_error(err))?;
+        self.flush()?;
 
-        self.inner.write_all(&self.temp[..encode_length])?;
+        let mut inner = self.inner.take().expect(TAKEN);
 
-        self.buf_length = 0;
+        inner.flush()?;
 
-        Ok(())
+        Ok(inner)
     }
 }
 
-impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write
-    for ToBase64Writer<W, N>
-{
-    fn write(&mut self, mut buf: &[u8]) -> Result<usize, io::Error> {
-        let original_buf_length = buf.len();
-
-        if self.buf_length == 0 {
-            while buf.len() >= 3 {
-                let max_available_buf_length =
-                    (buf.len() - (buf.len() % 3)).min((N::USIZE >> 2) * 3); // (N::USIZE / 4) * 3
-
-                let encode_length = self.engine.encode_slice(
-                    buf[..max_available_buf_length].as_ref(),
-                    &mut self.temp,
-                ).map_err(|err| super::to_io_error(err))?;
+impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> ToBase64Writer<W, N> {
+    fn write_pending(&mut self) -> Result<(), io::Error> {
+        let pending = self.encoder.pending();
 
-                buf = &buf[max_available_buf_length..];
+        let pending_length = pending.len();
 
-                self.inner.write_all(&self.temp[..encode_length])?;
+        if pending_length > 0 {
+            if self.flush_threshold > 0 {
+                self.output.extend_from_slice(pending);
+            } else {
+                self.inner.as_mut().expect(TAKEN).write_all(pending)?;
             }
 
-            let buf_length = buf.len();
-
-            if buf_length > 0 {
-                unsafe {
-                    copy_nonoverlapping(buf.as_ptr(), self.buf.as_mut_ptr(), buf_length);
-                }
+            self.encoder.consume(pending_length);
+        }
 
-                self.buf_length = buf_length;
-            }
-        } else {
-            debug_assert!(self.buf_length < 3);
+        if self.output.len() >= self.flush_threshold {
+            self.write_output()?;
+        }
 
-            let r = 3 - self.buf_length;
+        Ok(())
+    }
 
-            let buf_length = buf.len();
+    fn write_output(&mut self) -> Result<(), io::Error> {
+        if !self.output.is_empty() {
+            self.inner.as_mut().expect(TAKEN).write_all(&self.output)?;
 
-            let drain_length = r.min(buf_length);
+            self.output.clear();
+        }
 
-            unsafe {
-                copy_nonoverlapping(
-                    buf.as_ptr(),
-                    self.buf.as_mut_ptr().add(self.buf_length),
-                    drain_length,
-                );
-            }
+        Ok(())
+    }
+}
 
-            buf = &buf[drain_length..];
+impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Write
+    for ToBase64Writer<W, N>
+{
+    fn write(&mut self, mut buf: &[u8]) -> Result<usize, io::Error> {
+        let original_buf_length = buf.len();
 
-            self.buf_length += drain_length;
+        while !buf.is_empty() {
+            let c = self.encoder.encode(buf)?;
 
-            if self.buf_length == 3 {
-                self.drain_block()?;
+            buf = &buf[c..];
 
-                if buf_length > r {
-                    self.write_all(buf)?;
-                }
-            }
+            self.write_pending()?;
         }
 
         Ok(original_buf_length)
     }
 
     fn flush(&mut self) -> Result<(), io::Error> {
-        if self.buf_length > 0 {
-            self.drain_block()?;
-        }
+        self.encoder.finish()?;
 
-        Ok(())
+        self.write_pending()?;
+
+        self.write_output()
+    }
+}
+
+impl<W: Write, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Drop
+    for ToBase64Writer<W, N>
+{
+    /// Encode and write the last incomplete block if it has not been flushed. Errors are ignored,
+    /// so call `finish` to handle them.
+    #[inline]
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.flush();
+        }
     }
 }
 
@@ -0,0 +1,108 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::ArrayLength;
+
+use ::tokio::io::AsyncWrite;
+
+use crate::encoder::Encoder;
+
+/// Write any data and encode them to base64 data into an asynchronous writer.
+///
+/// The last incomplete block is encoded by `poll_flush` or `poll_shutdown`, so flush or shut
+/// down this writer only when the full plain data has been written.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct AsyncToBase64Writer<
+    W: AsyncWrite + Unpin,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    #[educe(Debug(ignore))]
+    inner: W,
+    encoder: Encoder<N>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncToBase64Writer<W> {
+    #[inline]
+    pub fn new(writer: W) -> AsyncToBase64Writer<W> {
+        Self::new2(writer, &base64::engine::general_purpose::STANDARD)
+    }
+}
+
+impl<W: AsyncWrite + Unpin, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>>
+    AsyncToBase64Writer<W, N>
+{
+    #[inline]
+    pub fn new2(
+        writer: W,
+        engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> AsyncToBase64Writer<W, N> {
+        AsyncToBase64Writer {
+            inner: writer,
+            encoder: Encoder::new(engine),
+        }
+    }
+
+    /// Get a reference to the inner writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: AsyncWrite + Unpin, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> AsyncWrite
+    for AsyncToBase64Writer<W, N>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let this = self.get_mut();
+
+        let inner = &mut this.inner;
+
+        this.encoder.poll_encode(buf, |pending| Pin::new(&mut *inner).poll_write(cx, pending))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        let this = self.get_mut();
+
+        let inner = &mut this.inner;
+
+        match this.encoder.poll_finish(|pending| Pin::new(&mut *inner).poll_write(cx, pending)) {
+            Poll::Ready(Ok(())) => (),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut *this).poll_flush(cx) {
+            Poll::Ready(Ok(())) => (),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> From<W> for AsyncToBase64Writer<W> {
+    #[inline]
+    fn from(writer: W) -> Self {
+        AsyncToBase64Writer::new(writer)
+    }
+}
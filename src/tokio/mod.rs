@@ -0,0 +1,7 @@
+//! Asynchronous readers and writers for Tokio, available with the `tokio` feature.
+
+mod async_from_base64_reader;
+mod async_to_base64_writer;
+
+pub use async_from_base64_reader::*;
+pub use async_to_base64_writer::*;
@@ -0,0 +1,166 @@
+use std::io::{self, ErrorKind, Read};
+
+use generic_array::typenum::{IsGreaterOrEqual, True, U4, U4096};
+use generic_array::{ArrayLength, GenericArray};
+
+use base64::Engine;
+
+use crate::decoder::Decoder;
+
+/// Read base64 data and re-encode them with another engine, e.g. to convert standard base64 data
+/// to base64url data or to normalize its padding.
+///
+/// It is done in one pass over one scratch buffer: the base64 data is decoded into the buffer,
+/// and encoded from it straight into the buffer given to `read`, so the plain data is neither
+/// returned by a decoding reader nor copied into an encoding reader in between.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct TranscodeReader<
+    R: Read,
+    N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True> = U4096,
+> {
+    #[educe(Debug(ignore))]
+    inner: R,
+    decoder: Decoder<N>,
+    /// The decoded data waiting to be encoded again.
+    #[educe(Debug(ignore))]
+    buf: GenericArray<u8, N>,
+    buf_offset: usize,
+    buf_length: usize,
+    /// The encoded bytes of a block which did not fit in the last read buffer.
+    temp: [u8; 4],
+    temp_length: usize,
+    #[educe(Debug(ignore))]
+    to_engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    /// Whether all base64 data has been decoded.
+    decoded_all: bool,
+}
+
+impl<R: Read> TranscodeReader<R> {
+    /// Create a reader which decodes with `from_engine` and encodes with `to_engine`.
+    #[inline]
+    pub fn new(
+        reader: R,
+        from_engine: &'static base64::engine::general_purpose::GeneralPurpose,
+        to_engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> TranscodeReader<R> {
+        Self::new2(reader, from_engine, to_engine)
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> TranscodeReader<R, N> {
+    #[inline]
+    pub fn new2(
+        reader: R,
+        from_engine: &'static base64::engine::general_purpose::GeneralPurpose,
+        to_engine: &'static base64::engine::general_purpose::GeneralPurpose,
+    ) -> TranscodeReader<R, N> {
+        TranscodeReader {
+            inner: reader,
+            decoder: Decoder::new(from_engine),
+            buf: GenericArray::default(),
+            buf_offset: 0,
+            buf_length: 0,
+            temp: [0; 4],
+            temp_length: 0,
+            to_engine,
+            decoded_all: false,
+        }
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> TranscodeReader<R, N> {
+    /// Decode until at least a whole group of 3 bytes is buffered, or all data has been decoded.
+    fn fill(&mut self) -> Result<(), io::Error> {
+        while self.buf_length < 3 && !self.decoded_all {
+            // at most 2 bytes are left, which are moved to the front to make room
+            self.buf.copy_within(self.buf_offset..(self.buf_offset + self.buf_length), 0);
+            self.buf_offset = 0;
+
+            let spare = &mut self.buf[self.buf_length..];
+
+            let c = loop {
+                if !self.decoder.needs_data() {
+                    break self.decoder.decode(spare)?;
+                }
+
+                match self.inner.read(self.decoder.spare()) {
+                    Ok(0) => break self.decoder.decode_end(spare)?,
+                    Ok(c) => self.decoder.fill(c)?,
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            };
+
+            if c == 0 {
+                self.decoded_all = true;
+            }
+
+            self.buf_length += c;
+        }
+
+        Ok(())
+    }
+
+    fn encode(&mut self, input_length: usize, output: &mut [u8]) -> Result<usize, io::Error> {
+        let input = &self.buf[self.buf_offset..(self.buf_offset + input_length)];
+
+        let encode_length =
+            self.to_engine.encode_slice(input, output).map_err(crate::to_io_error)?;
+
+        self.buf_offset += input_length;
+        self.buf_length -= input_length;
+
+        Ok(encode_length)
+    }
+}
+
+impl<R: Read, N: ArrayLength<u8> + IsGreaterOrEqual<U4, Output = True>> Read
+    for TranscodeReader<R, N>
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.temp_length > 0 {
+            let drain_length = buf.len().min(self.temp_length);
+
+            buf[..drain_length].copy_from_slice(&self.temp[..drain_length]);
+
+            self.temp.copy_within(drain_length..self.temp_length, 0);
+
+            self.temp_length -= drain_length;
+
+            return Ok(drain_length);
+        }
+
+        self.fill()?;
+
+        if self.buf_length == 0 {
+            return Ok(0);
+        }
+
+        // whole groups are encoded into `buf` directly, as many as fit
+        let drain_length = (self.buf_length - self.buf_length % 3).min((buf.len() >> 2) * 3);
+
+        if drain_length > 0 {
+            return self.encode(drain_length, buf);
+        }
+
+        // a single group, which does not fit in `buf` or is the last one, goes through `temp`
+        let mut b = [0; 4];
+
+        let encode_length = self.encode(self.buf_length.min(3), &mut b)?;
+
+        let copy_length = buf.len().min(encode_length);
+
+        buf[..copy_length].copy_from_slice(&b[..copy_length]);
+
+        self.temp_length = encode_length - copy_length;
+
+        self.temp[..self.temp_length].copy_from_slice(&b[copy_length..encode_length]);
+
+        Ok(copy_length)
+    }
+}
@@ -0,0 +1,174 @@
+use base64_stream::{Decoder, DecoderError};
+
+#[test]
+fn decode_manually() {
+    let base64 = b"SGkgdGhlcmUh";
+
+    let mut decoder: Decoder = Decoder::new(&base64::engine::general_purpose::STANDARD);
+
+    assert!(decoder.needs_data());
+
+    decoder.spare()[..base64.len()].copy_from_slice(base64);
+    decoder.fill(base64.len()).unwrap();
+
+    let mut test_data = [0u8; 16];
+
+    let c = decoder.decode(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!", &test_data[..c]);
+    assert!(decoder.needs_data());
+    assert_eq!(0, decoder.decode_end(&mut test_data).unwrap());
+}
+
+#[test]
+fn decode_manually_error() {
+    let base64 = b"SGkg*GhlcmUh";
+
+    let mut decoder: Decoder = Decoder::new(&base64::engine::general_purpose::STANDARD);
+
+    decoder.spare()[..base64.len()].copy_from_slice(base64);
+    decoder.fill(base64.len()).unwrap();
+
+    let mut test_data = [0u8; 16];
+
+    match decoder.decode(&mut test_data).unwrap_err() {
+        DecoderError::Decode(err) => assert_eq!(4, err.offset()),
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn decode_manually_invariants() {
+    use base64_stream::generic_array::typenum::U8;
+
+    let mut base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2Uu".as_ref();
+
+    let mut decoder: Decoder<U8> = Decoder::new(&base64::engine::general_purpose::STANDARD);
+
+    let mut buf = [0u8; 1];
+    let mut test_data = Vec::new();
+
+    loop {
+        decoder.debug_invariants();
+
+        let c = if !decoder.needs_data() {
+            decoder.decode(&mut buf).unwrap()
+        } else if base64.is_empty() {
+            decoder.decode_end(&mut buf).unwrap()
+        } else {
+            let spare = decoder.spare();
+            let length = spare.len().min(base64.len()).min(3);
+
+            spare[..length].copy_from_slice(&base64[..length]);
+            base64 = &base64[length..];
+
+            decoder.fill(length).unwrap();
+
+            continue;
+        };
+
+        if c == 0 {
+            break;
+        }
+
+        test_data.extend_from_slice(&buf[..c]);
+    }
+
+    assert_eq!(b"Hi there, this is a simple sentence.".as_ref(), test_data);
+}
+
+#[test]
+fn decode_manually_feed() {
+    use base64_stream::generic_array::typenum::U8;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2Uu";
+
+    let mut decoder: Decoder<U8> = Decoder::new(&base64::engine::general_purpose::STANDARD);
+
+    let mut buf = [0u8; 4];
+    let mut test_data = Vec::new();
+
+    for mut chunk in base64.chunks(5) {
+        while !chunk.is_empty() {
+            let c = decoder.feed(chunk).unwrap();
+
+            chunk = &chunk[c..];
+
+            let c = decoder.decode(&mut buf).unwrap();
+
+            test_data.extend_from_slice(&buf[..c]);
+        }
+    }
+
+    loop {
+        let c = decoder.decode_end(&mut buf).unwrap();
+
+        if c == 0 {
+            break;
+        }
+
+        test_data.extend_from_slice(&buf[..c]);
+    }
+
+    assert_eq!(b"Hi there, this is a simple sentence.".as_ref(), test_data);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn decode_manually_tiny_buffers_random() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+    use base64_stream::generic_array::typenum::U8;
+
+    // xorshift, so that the test is reproducible without extra crates
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+
+    let mut below = |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        (state % n as u64) as usize
+    };
+
+    for _ in 0..500 {
+        let data: Vec<u8> = (0..below(100)).map(|_| below(256) as u8).collect();
+
+        let base64 = STANDARD.encode(&data);
+        let mut base64 = base64.as_bytes();
+
+        let mut decoder: Decoder<U8> = Decoder::new(&STANDARD);
+
+        let mut buf = [0u8; 2];
+        let mut test_data = Vec::new();
+
+        loop {
+            decoder.debug_invariants();
+
+            let buf = &mut buf[..(1 + below(2))];
+
+            let c = if !decoder.needs_data() {
+                decoder.decode(buf).unwrap()
+            } else if base64.is_empty() {
+                decoder.decode_end(buf).unwrap()
+            } else {
+                let length = (1 + below(5)).min(base64.len());
+
+                let c = decoder.feed(&base64[..length]).unwrap();
+
+                base64 = &base64[c..];
+
+                continue;
+            };
+
+            if c == 0 {
+                break;
+            }
+
+            test_data.extend_from_slice(&buf[..c]);
+        }
+
+        assert_eq!(data, test_data);
+    }
+}
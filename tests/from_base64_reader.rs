@@ -1,7 +1,83 @@
-use std::io::{Cursor, Read};
+#![cfg_attr(feature = "read_buf", feature(read_buf, core_io_borrowed_buf))]
+
+use std::io::{BufRead, Cursor, Read};
 
 use base64_stream::FromBase64Reader;
 
+/// A reader which returns one byte per read call, like a slow socket.
+struct OneByteReader<'a>(&'a [u8]);
+
+impl Read for OneByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.split_first() {
+            Some((&b, rest)) if !buf.is_empty() => {
+                buf[0] = b;
+                self.0 = rest;
+
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+/// A reader which always fails, like a broken socket.
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+/// A reader which returns one byte per read call, failing with `Interrupted` before every byte,
+/// like a socket hit by signals.
+struct InterruptingReader<'a> {
+    inner: OneByteReader<'a>,
+    interrupt: bool,
+}
+
+impl Read for InterruptingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.interrupt = !self.interrupt;
+
+        if self.interrupt {
+            Err(std::io::ErrorKind::Interrupted.into())
+        } else {
+            self.inner.read(buf)
+        }
+    }
+}
+
+/// A non-blocking socket which has the chunks ready one at a time, failing with `WouldBlock`
+/// before every chunk but the first one.
+struct ChunkedReader<'a> {
+    chunks: std::slice::Iter<'a, &'a [u8]>,
+    ready: bool,
+}
+
+impl Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.ready = !self.ready;
+
+        if !self.ready {
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+
+        match self.chunks.next() {
+            Some(chunk) => {
+                buf[..chunk.len()].copy_from_slice(chunk);
+
+                Ok(chunk.len())
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// The test sentence, wrapped with `\r\n`, bare `\n` and bare `\r` line endings.
+const MIXED_LINE_ENDINGS: &[u8] = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVu\r\nY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlz\nIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2Vz\rIGFyZSBjb3JyZWN0Lg==\n";
+
 #[test]
 fn decode_exact() {
     let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
@@ -40,3 +116,1989 @@ fn decode_to_string() {
 
     assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
 }
+
+#[test]
+fn decode_url_safe() {
+    let base64 = b"-_----8=".to_vec();
+
+    let mut reader = FromBase64Reader::new_with_engine(
+        Cursor::new(base64),
+        &base64_stream::base64::engine::general_purpose::URL_SAFE,
+    );
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(vec![0xFB, 0xFF, 0xBE, 0xFB, 0xEF], test_data);
+}
+
+#[test]
+fn decode_custom_engine() {
+    use base64_stream::base64::alphabet::CRYPT;
+    use base64_stream::base64::engine::general_purpose::{GeneralPurpose, NO_PAD};
+    use base64_stream::base64::Engine;
+
+    const CRYPT_NO_PAD: GeneralPurpose = GeneralPurpose::new(&CRYPT, NO_PAD);
+
+    let base64 = CRYPT_NO_PAD.encode(b"Hi there, this is a simple sentence.");
+
+    let mut reader = FromBase64Reader::new_with_engine(Cursor::new(base64), &CRYPT_NO_PAD);
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence.", test_data);
+}
+
+#[test]
+fn decode_crypt() {
+    let mut test_data = Vec::new();
+
+    // `.`, `/`, `0` and `1` are 0, 1, 2 and 3 in crypt(3)
+    FromBase64Reader::new_crypt(b"./01zk".as_ref()).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(vec![0x00, 0x10, 0x83, 0xFF], test_data);
+
+    test_data.clear();
+
+    // and `A` and `B` are in bcrypt
+    FromBase64Reader::new_bcrypt(b"./AB9u".as_ref()).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(vec![0x00, 0x10, 0x83, 0xFF], test_data);
+
+    // padding is not accepted
+    assert!(FromBase64Reader::new_crypt(b"./01zk==".as_ref())
+        .read_to_end(&mut test_data)
+        .is_err());
+}
+
+#[test]
+fn decode_with_tables() {
+    use base64_stream::base64::alphabet::Alphabet;
+    use base64_stream::base64::engine::general_purpose::{GeneralPurpose, NO_PAD};
+    use base64_stream::base64::Engine;
+
+    // the standard alphabet backwards
+    let symbols = "/+9876543210zyxwvutsrqponmlkjihgfedcbaZYXWVUTSRQPONMLKJIHGFEDCBA";
+
+    let mut decode_table = [0xFFu8; 256];
+
+    for (value, &b) in symbols.as_bytes().iter().enumerate() {
+        decode_table[b as usize] = value as u8;
+    }
+
+    let engine = GeneralPurpose::new(&Alphabet::new(symbols).unwrap(), NO_PAD);
+
+    let base64 = engine.encode(b"Hi there, this is a simple sentence.!");
+
+    let mut reader = FromBase64Reader::new_with_tables(Cursor::new(base64), &decode_table).unwrap();
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence.!", test_data);
+
+    // a byte which maps to the invalid value is rejected
+    let mut reader = FromBase64Reader::new_with_tables(b"SGk-".as_ref(), &decode_table).unwrap();
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+
+    let mut duplicated = decode_table;
+
+    duplicated[b'-' as usize] = 0;
+
+    let mut two_invalid_values = decode_table;
+
+    two_invalid_values[b'-' as usize] = 0xFE;
+
+    let mut not_ascii = decode_table;
+
+    not_ascii[b'/' as usize] = 0xFF;
+    not_ascii[0x80] = 0;
+
+    for table in [duplicated, two_invalid_values, not_ascii] {
+        let err = FromBase64Reader::new_with_tables(b"".as_ref(), &table).unwrap_err();
+
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    }
+}
+
+#[test]
+fn decode_ignoring_whitespace() {
+    let base64 = "SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==";
+
+    let wrapped = base64
+        .as_bytes()
+        .chunks(76)
+        .map(|line| format!("{}\r\n", std::str::from_utf8(line).unwrap()))
+        .collect::<String>();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64.as_bytes().to_vec()));
+
+    let mut expected = Vec::new();
+
+    reader.read_to_end(&mut expected).unwrap();
+
+    let mut reader = FromBase64Reader::new_ignoring_whitespace(Cursor::new(wrapped.into_bytes()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(expected, test_data);
+}
+
+#[test]
+fn decode_ignoring_bom() {
+    let base64 = b"\xEF\xBB\xBFSGkgdGhl\r\ncmUh\r\n".as_ref();
+
+    let mut test_data = Vec::new();
+
+    FromBase64Reader::new_ignoring_whitespace(base64).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!", test_data.as_slice());
+
+    // the BOM may arrive over several reads
+    test_data.clear();
+
+    FromBase64Reader::new_ignoring_whitespace(OneByteReader(base64))
+        .read_to_end(&mut test_data)
+        .unwrap();
+
+    assert_eq!(b"Hi there!", test_data.as_slice());
+
+    // only a leading BOM is skipped, and only when whitespaces are ignored
+    assert!(FromBase64Reader::new_ignoring_whitespace(b"SGkg\xEF\xBB\xBFdGhlcmUh".as_ref())
+        .read_to_end(&mut Vec::new())
+        .is_err());
+    assert!(FromBase64Reader::new_ignoring_whitespace(b"\xEF\xBBSGkgdGhlcmUh".as_ref())
+        .read_to_end(&mut Vec::new())
+        .is_err());
+    assert!(FromBase64Reader::new(base64).read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_into_inner() {
+    let base64 = b"SGkgdGhlcmUs".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut test_data = [0u8; 3];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi ", &test_data);
+
+    let (inner, leftover) = reader.into_inner();
+
+    assert_eq!(12, inner.position());
+    assert_eq!(b"dGhlcmUs".to_vec(), leftover);
+}
+
+#[test]
+fn decode_get_ref_and_get_mut() {
+    let base64 = b"SGkgdGhlcmUs".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    assert_eq!(0, reader.get_ref().position());
+
+    reader.get_mut().set_position(4);
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"there,".to_vec(), test_data);
+}
+
+#[test]
+fn decode_lines() {
+    let base64 = b"SGkgdGhlcmUsCnRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UKdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLgo=".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut first_line = String::new();
+
+    reader.read_line(&mut first_line).unwrap();
+
+    assert_eq!("Hi there,\n", first_line);
+
+    let mut test_data = [0u8; 4];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"this", &test_data);
+
+    let lines = reader.lines().collect::<Result<Vec<String>, _>>().unwrap();
+
+    assert_eq!(vec![" is a simple sentence", "used for testing this crate."], lines);
+}
+
+#[test]
+fn decode_no_pad() {
+    for (base64, expected) in [
+        (b"SGkgdGhlcmU".as_ref(), b"Hi there".as_ref()),
+        (b"SGkgdGhlcmU=".as_ref(), b"Hi there".as_ref()),
+        (b"SGkgdGhlcmUh".as_ref(), b"Hi there!".as_ref()),
+    ] {
+        let mut reader = FromBase64Reader::new_no_pad(Cursor::new(base64.to_vec()));
+
+        let mut test_data = Vec::new();
+
+        reader.read_to_end(&mut test_data).unwrap();
+
+        assert_eq!(expected, test_data);
+    }
+
+    let mut reader = FromBase64Reader::new_no_pad(Cursor::new(b"SGkgdGhlcmUhS".to_vec()));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_truncated() {
+    use base64_stream::base64::DecodeError;
+    use base64_stream::FromBase64Error;
+
+    for (base64, expected) in [
+        (b"SGkgd".as_ref(), DecodeError::InvalidLength),
+        (b"SGkgdG".as_ref(), DecodeError::InvalidPadding),
+        (b"SGkgdGh".as_ref(), DecodeError::InvalidPadding),
+    ] {
+        let mut reader = FromBase64Reader::new(Cursor::new(base64.to_vec()));
+
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+        let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+        assert_eq!(4, err.offset());
+        assert_eq!(&expected, err.error());
+    }
+
+    let mut reader = FromBase64Reader::new_no_pad(Cursor::new(b"SGkgd".to_vec()));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_byte_by_byte() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut test_data = Vec::new();
+
+    let mut byte = [0u8; 1];
+
+    while reader.read(&mut byte).unwrap() > 0 {
+        test_data.push(byte[0]);
+    }
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[test]
+fn decode_buffer_sizes() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+    use base64_stream::generic_array::typenum::{U4, U64, U65536};
+
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".repeat(100);
+
+    let base64 = STANDARD.encode(&test_data).into_bytes();
+
+    let mut reader: FromBase64Reader<_, U4> = FromBase64Reader::new2(Cursor::new(&base64), &STANDARD);
+
+    let mut decoded = Vec::new();
+
+    reader.read_to_end(&mut decoded).unwrap();
+
+    assert_eq!(test_data, decoded);
+
+    let mut reader: FromBase64Reader<_, U64> = FromBase64Reader::new2(Cursor::new(&base64), &STANDARD);
+
+    let mut decoded = Vec::new();
+
+    reader.read_to_end(&mut decoded).unwrap();
+
+    assert_eq!(test_data, decoded);
+
+    let mut reader: FromBase64Reader<_, U65536> =
+        FromBase64Reader::new2(Cursor::new(&base64), &STANDARD);
+
+    let mut decoded = Vec::new();
+
+    reader.read_to_end(&mut decoded).unwrap();
+
+    assert_eq!(test_data, decoded);
+}
+
+#[test]
+fn decode_compaction() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+    use base64_stream::generic_array::typenum::U8;
+
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".repeat(10);
+
+    let base64 = STANDARD.encode(&test_data).into_bytes();
+
+    // reading one block at a time makes the buffer compact while a block is still buffered
+    let mut reader: FromBase64Reader<_, U8> = FromBase64Reader::new2(Cursor::new(&base64), &STANDARD);
+
+    let mut buf = [0u8; 3];
+    let mut decoded = Vec::new();
+
+    loop {
+        let c = reader.read(&mut buf).unwrap();
+
+        if c == 0 {
+            break;
+        }
+
+        decoded.extend_from_slice(&buf[..c]);
+    }
+
+    assert_eq!(test_data, decoded);
+}
+
+#[test]
+fn decode_counts() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    assert_eq!(0, reader.decoded_count());
+    assert_eq!(0, reader.consumed_count());
+
+    let mut test_data = [0u8; 9];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(9, reader.decoded_count());
+
+    reader.read_to_end(&mut Vec::new()).unwrap();
+
+    assert_eq!(94, reader.decoded_count());
+    assert_eq!(128, reader.consumed_count());
+}
+
+#[test]
+fn decode_error_offset() {
+    use base64_stream::base64::DecodeError;
+    use base64_stream::FromBase64Error;
+
+    let mut base64 = b"SGkgdGhlcmUs".repeat(400);
+
+    base64[4100] = b'*';
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+    let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+    assert_eq!(4100, err.offset());
+    assert!(matches!(err.error(), DecodeError::InvalidByte(_, b'*')));
+}
+
+#[test]
+fn decode_error_offset_stripped() {
+    use base64_stream::FromBase64Error;
+
+    fn offset(mut reader: impl Read) -> u64 {
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+        err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap().offset()
+    }
+
+    // the offsets count the stripped bytes before the error, but not the bytes after it
+    for (base64, expected) in [
+        (b"SG!s\n\n\n\nSGVs".as_ref(), 2),
+        (b"SGVs\r\nbG!s", 8),
+        (b"\xEF\xBB\xBFSG\n!s", 6),
+    ] {
+        let reader = FromBase64Reader::new_ignoring_whitespace(Cursor::new(base64));
+
+        assert_eq!(expected, offset(reader));
+
+        let reader = FromBase64Reader::new_ignoring_whitespace(OneByteReader(base64));
+
+        assert_eq!(expected, offset(reader));
+    }
+
+    assert_eq!(6, offset(FromBase64Reader::new_nul_padded(Cursor::new(b"SGVsbG!=\0\0\0"))));
+
+    assert_eq!(2, offset(FromBase64Reader::new_lenient(Cursor::new(b"SG=s!bG8="))));
+
+    assert_eq!(12, offset(FromBase64Reader::new_lenient(Cursor::new(b"S!G!V!s!b!G!=!s"))));
+
+    // across several buffers of wrapped data
+    let mut base64 = b"SGkgdGhlcmUs".repeat(400).chunks(76).collect::<Vec<_>>().join(&b"\r\n"[..]);
+
+    base64[4100] = b'*';
+
+    assert_eq!(4100, offset(FromBase64Reader::new_ignoring_whitespace(Cursor::new(base64))));
+}
+
+#[test]
+fn decode_error_window() {
+    use base64_stream::FromBase64Error;
+
+    let mut base64 = b"SGkgdGhlcmUs".repeat(400);
+
+    base64[4102] = b'!';
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+    let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+    assert_eq!(b"cm!s", err.window());
+    assert!(err.to_string().starts_with("invalid base64 data at byte 4102 in window \"cm!s\": "));
+
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgd".to_vec()));
+
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+    let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+    assert_eq!(b"d", err.window());
+}
+
+#[test]
+fn decode_reset() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh".to_vec()));
+
+    let mut test_data = [0u8; 7];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi ther", &test_data);
+
+    reader.reset(Cursor::new(b"dGhpcyBpcyBh".to_vec()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"this is a".to_vec(), test_data);
+    assert_eq!(9, reader.decoded_count());
+}
+
+#[test]
+fn decode_bytes_decoded() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let test_data = FromBase64Reader::new(Cursor::new(base64))
+        .bytes_decoded()
+        .collect::<Result<Vec<u8>, _>>()
+        .unwrap();
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+
+    let mut bytes = FromBase64Reader::new(Cursor::new(b"SGk*".to_vec())).bytes_decoded();
+
+    assert!(bytes.next().unwrap().is_err());
+}
+
+#[test]
+fn decoded_len_estimate() {
+    assert_eq!(0, base64_stream::decoded_len_estimate(0));
+    assert_eq!(3, base64_stream::decoded_len_estimate(3));
+    assert_eq!(3, base64_stream::decoded_len_estimate(4));
+    assert_eq!(6, base64_stream::decoded_len_estimate(5));
+    assert!(base64_stream::decoded_len_estimate(128) >= 94);
+}
+
+#[test]
+fn is_valid_base64() {
+    use base64_stream::base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE};
+    use base64_stream::is_valid_base64;
+
+    assert!(is_valid_base64(b"", &STANDARD));
+    assert!(is_valid_base64(b"SGkgdGhlcmUh", &STANDARD));
+    assert!(is_valid_base64(b"SGk=", &STANDARD));
+    assert!(is_valid_base64(b"SA==", &STANDARD));
+    assert!(is_valid_base64(b"SGk", &STANDARD_NO_PAD));
+    assert!(is_valid_base64(b"-_-_", &URL_SAFE));
+
+    // the alphabet
+    assert!(!is_valid_base64(b"-_-_", &STANDARD));
+    assert!(!is_valid_base64(b"SGkg dGhl", &STANDARD));
+    // the padding
+    assert!(!is_valid_base64(b"SGk", &STANDARD));
+    assert!(!is_valid_base64(b"SGk=", &STANDARD_NO_PAD));
+    assert!(!is_valid_base64(b"S===", &STANDARD));
+    assert!(!is_valid_base64(b"SG=k", &STANDARD));
+    assert!(!is_valid_base64(b"SGk=SGk=", &STANDARD));
+    // the length and the trailing bits
+    assert!(!is_valid_base64(b"SGkgd", &STANDARD_NO_PAD));
+    assert!(!is_valid_base64(b"SGl=", &STANDARD));
+}
+
+#[test]
+fn validate() {
+    use base64_stream::base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+    use base64_stream::validate;
+
+    let mut base64 = b"SGkgdGhlcmUs".repeat(1000);
+
+    assert_eq!(Ok(()), validate(Cursor::new(&base64), &STANDARD).unwrap());
+
+    base64[10000] = b'*';
+
+    assert_eq!(Err(10000), validate(Cursor::new(&base64), &STANDARD).unwrap());
+
+    // the padding is checked too
+    assert_eq!(Ok(()), validate(b"SGkgdGhlcmU=".as_ref(), &STANDARD).unwrap());
+    assert_eq!(Err(8), validate(b"SGkgdGhlcmU".as_ref(), &STANDARD).unwrap());
+    assert_eq!(Err(8), validate(b"SGkgdGhlcmU=".as_ref(), &STANDARD_NO_PAD).unwrap());
+    // a `=` in the middle is an invalid byte
+    assert_eq!(Err(3), validate(b"SGk=dGhl".as_ref(), &STANDARD).unwrap());
+}
+
+#[test]
+fn count_decoded() {
+    use base64_stream::base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+    use base64_stream::count_decoded;
+
+    let mut base64 = b"SGkgdGhlcmUs".repeat(1000);
+
+    assert_eq!(Ok(9000), count_decoded(Cursor::new(&base64), &STANDARD).unwrap());
+
+    base64[10000] = b'*';
+
+    assert_eq!(Err(10000), count_decoded(Cursor::new(&base64), &STANDARD).unwrap());
+
+    assert_eq!(Ok(0), count_decoded(b"".as_ref(), &STANDARD).unwrap());
+    assert_eq!(Ok(8), count_decoded(b"SGkgdGhlcmU=".as_ref(), &STANDARD).unwrap());
+    assert_eq!(Ok(8), count_decoded(b"SGkgdGhlcmU".as_ref(), &STANDARD_NO_PAD).unwrap());
+    assert_eq!(Err(8), count_decoded(b"SGkgdGhlcmU".as_ref(), &STANDARD).unwrap());
+}
+
+#[test]
+fn decoded_len() {
+    use std::io::{Seek, SeekFrom};
+
+    use base64_stream::decoded_len;
+
+    for (base64, expected) in [
+        (b"".as_ref(), 0),
+        (b"SGkgdGhlcmUh".as_ref(), 9),
+        (b"SGkgdGhlcmU=".as_ref(), 8),
+        (b"SGkgdGhlcmU".as_ref(), 8),
+        (b"SGkgdGhlcg==".as_ref(), 7),
+        (b"SGkgdGhlcg".as_ref(), 7),
+    ] {
+        let mut reader = Cursor::new(base64);
+
+        assert_eq!(expected, decoded_len(&mut reader).unwrap());
+        assert_eq!(0, reader.position());
+    }
+
+    // the rest of the stream is measured, and the position is kept
+    let mut reader = Cursor::new(b"header:SGkgdGhlcmUh".as_ref());
+
+    reader.seek(SeekFrom::Start(7)).unwrap();
+
+    assert_eq!(9, decoded_len(&mut reader).unwrap());
+    assert_eq!(7, reader.position());
+
+    // wrapped data are rejected rather than measured by their length
+    for base64 in [
+        b"SGkgdGhl\ncmUh\n".as_ref(),
+        b"SGVs\nbG8=",
+        b"SGVs bG8=",
+        b"SGk=SGk=",
+        b"SGkgdGhlc===",
+        b"SGkgd",
+        // padding which does not complete the last block
+        b"QUJDRA=",
+        b"QQ=",
+        b"QUJDRA===",
+    ] {
+        let mut reader = Cursor::new(base64);
+
+        let err = decoded_len(&mut reader).unwrap_err();
+
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(0, reader.position());
+    }
+}
+
+#[test]
+fn recommended_buffer_size() {
+    assert_eq!(8, base64_stream::recommended_buffer_size(3));
+    assert_eq!(12, base64_stream::recommended_buffer_size(4));
+    assert_eq!(10928, base64_stream::recommended_buffer_size(8192));
+}
+
+#[test]
+fn buffer_size() {
+    use base64_stream::generic_array::typenum::{Unsigned, U4};
+
+    assert_eq!(4096, base64_stream::BUFFER_SIZE);
+    assert_eq!(U4::USIZE, base64_stream::min_buffer_size());
+}
+
+#[test]
+fn decode_to_vec() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+    let test_data = base64_stream::decode_to_vec(base64).unwrap();
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[test]
+fn decode_to_end_after_fill_buf() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    assert_eq!(b"Hi", &reader.fill_buf().unwrap()[..2]);
+
+    reader.consume(2);
+
+    let mut test_data = Vec::new();
+
+    assert_eq!(92, reader.read_to_end(&mut test_data).unwrap());
+
+    assert_eq!(b" there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[test]
+fn decode_auto() {
+    for (base64, expected) in [
+        (b"-_----8=".as_ref(), vec![0xFB, 0xFF, 0xBE, 0xFB, 0xEF]),
+        (b"+/++++8=".as_ref(), vec![0xFB, 0xFF, 0xBE, 0xFB, 0xEF]),
+        (b"SGkgdGhlcmUh".as_ref(), b"Hi there!".to_vec()),
+    ] {
+        let mut reader = FromBase64Reader::new_auto(Cursor::new(base64.to_vec()));
+
+        let mut test_data = Vec::new();
+
+        reader.read_to_end(&mut test_data).unwrap();
+
+        assert_eq!(expected, test_data);
+    }
+
+    let mut reader = FromBase64Reader::new_auto(Cursor::new(b"-_-+".to_vec()));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_data_uri() {
+    for base64 in [
+        b"data:text/plain;base64,SGkgdGhlcmUh".as_ref(),
+        b"data:;base64,SGkgdGhlcmUh".as_ref(),
+        b"DATA:image/svg+xml;charset=utf-8;BASE64,SGkgdGhlcmUh".as_ref(),
+        b"SGkgdGhlcmUh".as_ref(),
+    ] {
+        let mut reader = FromBase64Reader::new_data_uri(Cursor::new(base64.to_vec()));
+
+        let mut test_data = Vec::new();
+
+        reader.read_to_end(&mut test_data).unwrap();
+
+        assert_eq!(b"Hi there!".to_vec(), test_data);
+
+        let mut reader = FromBase64Reader::new_data_uri(OneByteReader(base64));
+
+        let mut test_data = Vec::new();
+
+        reader.read_to_end(&mut test_data).unwrap();
+
+        assert_eq!(b"Hi there!".to_vec(), test_data);
+    }
+
+    for base64 in [b"data:text/plain,Hi there!".as_ref(), b"data:text/plain;base64".as_ref()] {
+        let mut reader = FromBase64Reader::new_data_uri(Cursor::new(base64.to_vec()));
+
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+}
+
+#[test]
+fn decode_exact_one_byte_at_a_time() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+    let mut reader = FromBase64Reader::new(OneByteReader(base64));
+
+    let mut test_data = [0u8; 94];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_bytes(), test_data.as_ref());
+
+    assert_eq!(0, reader.read(&mut [0u8; 1]).unwrap());
+}
+
+#[test]
+fn decode_temp_without_reading() {
+    struct WouldBlockReader;
+
+    impl Read for WouldBlockReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    let mut reader = FromBase64Reader::new(b"SGkh".chain(WouldBlockReader));
+
+    let mut test_data = [0u8; 1];
+
+    for expected in b"Hi!" {
+        assert_eq!(1, reader.read(&mut test_data).unwrap());
+        assert_eq!(*expected, test_data[0]);
+    }
+
+    assert_eq!(
+        std::io::ErrorKind::WouldBlock,
+        reader.read(&mut test_data).unwrap_err().kind()
+    );
+}
+
+#[test]
+fn decode_buffered_len() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh".to_vec()));
+
+    assert_eq!(0, reader.buffered_len());
+
+    let mut test_data = [0u8; 1];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(8, reader.buffered_len());
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(8, test_data.len());
+    assert_eq!(0, reader.buffered_len());
+}
+
+#[test]
+fn decode_with_limit() {
+    let mut reader = FromBase64Reader::with_limit(Cursor::new(b"SGkgdGhlcmUh".to_vec()), 9);
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!".to_vec(), test_data);
+
+    let mut reader = FromBase64Reader::with_limit(Cursor::new(b"SGkgdGhlcmUh".to_vec()), 5);
+
+    let mut test_data = [0u8; 9];
+
+    assert_eq!(5, reader.read(&mut test_data).unwrap());
+    assert_eq!(b"Hi th", &test_data[..5]);
+    assert!(reader.read(&mut test_data).is_err());
+
+    // seeking past the limit leaves nothing to read within it
+    let mut reader = FromBase64Reader::with_limit(Cursor::new(b"SGkgdGhlcmUh".to_vec()), 3);
+
+    reader.seek_decoded(6).unwrap();
+
+    assert!(reader.read(&mut test_data).is_err());
+
+    // the bytes before 7 in its block are beyond the limit too
+    assert!(reader.seek_decoded(7).is_err());
+}
+
+#[test]
+fn decode_clone() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut test_data = [0u8; 10];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    let mut cloned_reader = reader.clone();
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    let mut cloned_test_data = Vec::new();
+
+    cloned_reader.read_to_end(&mut cloned_test_data).unwrap();
+
+    assert_eq!(b"this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+    assert_eq!(test_data, cloned_test_data);
+}
+
+#[cfg(feature = "read_buf")]
+#[test]
+fn decode_read_buf() {
+    use std::io::BorrowedBuf;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    reader.fill_buf().unwrap();
+    reader.consume(3);
+
+    let mut test_data: Vec<u8> = Vec::with_capacity(1024);
+
+    loop {
+        let mut buf = BorrowedBuf::from(test_data.spare_capacity_mut());
+
+        reader.read_buf(buf.unfilled()).unwrap();
+
+        let c = buf.len();
+
+        if c == 0 {
+            break;
+        }
+
+        unsafe { test_data.set_len(test_data.len() + c) };
+    }
+
+    assert_eq!(b"there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[test]
+fn decode_seek() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut test_data = [0u8; 4];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    reader.seek_decoded(10).unwrap();
+
+    assert_eq!(10, reader.decoded_count());
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+
+    reader.seek_decoded(0).unwrap();
+
+    let mut test_data = [0u8; 8];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there", &test_data);
+
+    reader.seek_decoded(1000).unwrap();
+
+    assert_eq!(0, reader.read(&mut test_data).unwrap());
+}
+
+#[test]
+fn decode_seek_after_eof() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh"));
+
+    reader.read_to_end(&mut Vec::new()).unwrap();
+
+    assert!(reader.is_eof());
+
+    reader.seek_decoded(0).unwrap();
+
+    assert!(!reader.is_eof());
+    assert_eq!(None, reader.ended_cleanly());
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!".to_vec(), test_data);
+    assert!(reader.is_eof());
+}
+
+#[test]
+fn decode_seek_nul_padded() {
+    let mut reader = FromBase64Reader::new_nul_padded(Cursor::new(b"SGkgdGhlcmUh\0\0\0\0"));
+
+    reader.read_to_end(&mut Vec::new()).unwrap();
+
+    // the trailing NULs which have been met do not cut the data read again
+    reader.seek_decoded(3).unwrap();
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"there!".to_vec(), test_data);
+}
+
+#[test]
+fn decode_seek_unsupported() {
+    let base64 = b"SGkgdGhl\ncmUs".to_vec();
+
+    let mut reader = FromBase64Reader::new_ignoring_whitespace(Cursor::new(base64));
+
+    let error = reader.seek_decoded(3).unwrap_err();
+
+    assert_eq!(std::io::ErrorKind::Unsupported, error.kind());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn decode_to_bytes() {
+    use bytes::BytesMut;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+    let mut test_data = BytesMut::new();
+
+    assert_eq!(10, reader.read_to_bytes(&mut test_data, 10).unwrap());
+    assert_eq!(b"Hi there, ".as_ref(), test_data.as_ref());
+
+    while reader.read_to_bytes(&mut test_data, 1024).unwrap() > 0 {}
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref(), test_data.as_ref());
+}
+
+#[test]
+fn decode_mixed_line_endings() {
+    let mut reader = FromBase64Reader::new_ignoring_whitespace(Cursor::new(MIXED_LINE_ENDINGS));
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+
+    let mut reader = FromBase64Reader::new_lenient(Cursor::new(MIXED_LINE_ENDINGS));
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+}
+
+#[test]
+fn decode_lenient() {
+    let base64 = b"SGkg\r\n>dGhl\x00cmUh\n".to_vec();
+
+    let mut reader = FromBase64Reader::new_ignoring_whitespace(Cursor::new(base64.clone()));
+
+    let mut test_data = Vec::new();
+
+    assert!(reader.read_to_end(&mut test_data).is_err());
+
+    let mut reader = FromBase64Reader::new_lenient(Cursor::new(base64));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!".to_vec(), test_data);
+
+    // only the characters of the configured alphabet are kept
+    let mut test_data = Vec::new();
+
+    FromBase64Reader::new_lenient(Cursor::new(b"SG-Vs!bG8=")).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hello".to_vec(), test_data);
+
+    let mut test_data = Vec::new();
+
+    base64_stream::FromBase64ReaderBuilder::new()
+        .url_safe(true)
+        .lenient(true)
+        .build(Cursor::new(b"-_+/-_"))
+        .unwrap()
+        .read_to_end(&mut test_data)
+        .unwrap();
+
+    assert_eq!(vec![0xfb, 0xff, 0xbf], test_data);
+}
+
+#[test]
+fn decode_peek() {
+    use base64_stream::generic_array::typenum::U4;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader: FromBase64Reader<_, U4> =
+        FromBase64Reader::new2(OneByteReader(&base64), &base64::engine::general_purpose::STANDARD);
+
+    assert_eq!(b"H", reader.peek(1).unwrap());
+    assert_eq!(b"Hi there", reader.peek(8).unwrap());
+
+    let mut test_data = [0u8; 3];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi ", &test_data);
+    assert_eq!(b"there, this", reader.peek(11).unwrap());
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+    assert!(reader.peek(1).unwrap().is_empty());
+}
+
+#[test]
+fn decode_read_decoded() {
+    use base64_stream::base64::DecodeError;
+    use base64_stream::{DecoderError, ReadDecodedError};
+
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh".to_vec()));
+
+    let mut test_data = [0u8; 16];
+
+    assert_eq!(9, reader.read_decoded(&mut test_data).unwrap());
+
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmU".to_vec()));
+
+    let mut test_data = [0u8; 16];
+
+    assert_eq!(6, reader.read_decoded(&mut test_data).unwrap());
+
+    let err = reader.read_decoded(&mut test_data).unwrap_err();
+
+    assert!(matches!(err.decode_error(), Some(DecodeError::InvalidPadding)));
+
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGk*dGhlcmUh".to_vec()));
+
+    let err = reader.read_decoded(&mut test_data).unwrap_err();
+
+    assert!(matches!(err.decode_error(), Some(DecodeError::InvalidByte(3, b'*'))));
+
+    let mut reader = FromBase64Reader::with_limit(Cursor::new(b"SGkgdGhlcmUh".to_vec()), 3);
+
+    let mut test_data = [0u8; 3];
+
+    reader.read_decoded(&mut test_data).unwrap();
+
+    let err = reader.read_decoded(&mut test_data).unwrap_err();
+
+    assert!(matches!(err, ReadDecodedError::Decode(DecoderError::LimitExceeded(3))));
+
+    let mut reader = FromBase64Reader::new(FailingReader);
+
+    let err = reader.read_decoded(&mut test_data).unwrap_err();
+
+    assert!(matches!(err, ReadDecodedError::Io(ref err) if err.kind() == std::io::ErrorKind::BrokenPipe));
+}
+
+#[test]
+fn decode_skipping() {
+    let mut base64 = vec![0xFF, b'*', b'\n', 0x00, 0x01];
+
+    base64.extend_from_slice(b"SGkgdGhlcmUh");
+
+    let mut reader = FromBase64Reader::new_skipping(OneByteReader(&base64), 5);
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!".to_vec(), test_data);
+    assert_eq!(17, reader.consumed_count());
+
+    let mut reader = FromBase64Reader::new_skipping(Cursor::new(&base64), 5);
+
+    reader.seek_decoded(4).unwrap();
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"here!".to_vec(), test_data);
+}
+
+#[test]
+fn decode_documents() {
+    let base64 = b"SGkgdGhl\ncmUh\n-----\nSGkgdGhlcmUsIHRoaXMg\naXMgYSBzaW1wbGUgc2VudGVuY2Uu\n-----\nSGk=\n-----\n";
+
+    let mut reader = FromBase64Reader::new_documents(OneByteReader(base64), b"-----");
+
+    let mut documents = Vec::new();
+
+    loop {
+        let mut test_data = String::new();
+
+        reader.read_to_string(&mut test_data).unwrap();
+
+        documents.push(test_data);
+
+        if !reader.next_document().unwrap() {
+            break;
+        }
+    }
+
+    assert_eq!(vec!["Hi there!", "Hi there, this is a simple sentence.", "Hi"], documents);
+
+    let mut reader = FromBase64Reader::new_documents(Cursor::new(base64), b"-----");
+
+    let mut test_data = [0u8; 2];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert!(reader.next_document().unwrap());
+
+    let mut test_data = [0u8; 2];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi", &test_data);
+}
+
+#[test]
+fn decode_framed() {
+    // frames of base64 data, each after a 1-byte length prefix
+    let wire = b"\x04SGkh\x10dGhlcmUsIHRoaXMg\x0caXMgYSBzZW50\x03end".as_ref();
+
+    let mut reader = FromBase64Reader::new_framed(Cursor::new(&wire[1..]), u64::from(wire[0]));
+
+    let mut frames = Vec::new();
+
+    for _ in 0..3 {
+        let mut test_data = String::new();
+
+        reader.read_to_string(&mut test_data).unwrap();
+
+        frames.push(test_data);
+
+        let mut prefix = [0u8; 1];
+
+        reader.get_mut().get_mut().read_exact(&mut prefix).unwrap();
+
+        reader.get_mut().set_frame_len(u64::from(prefix[0]));
+        reader.next_frame().unwrap();
+    }
+
+    assert_eq!(vec!["Hi!", "there, this ", "is a sent"], frames);
+
+    // the inner reader is right after the last prefix
+    let mut rest = Vec::new();
+
+    reader.get_mut().get_mut().read_to_end(&mut rest).unwrap();
+
+    assert_eq!(b"end", rest.as_slice());
+
+    // the rest of a frame is skipped, and a truncated frame is an error
+    let mut reader = FromBase64Reader::new_framed(OneByteReader(b"SGkhSGkh"), 4);
+
+    let mut test_data = [0u8; 1];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    reader.next_frame().unwrap();
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi!", test_data);
+
+    reader.next_frame().unwrap();
+
+    assert!(reader.read_to_string(&mut test_data).is_err());
+}
+
+#[test]
+fn decode_with_crc32() {
+    use base64_stream::{DecoderError, ReadDecodedError};
+
+    // the CRC-32 check value of `123456789`
+    let mut reader = FromBase64Reader::with_crc32(OneByteReader(b"MTIzNDU2Nzg5"), 0xCBF4_3926);
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("123456789", test_data);
+
+    let mut reader = FromBase64Reader::with_crc32(Cursor::new(b"MTIzNDU2Nzg5"), 0);
+
+    let mut test_data = [0u8; 9];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    let err = reader.read_decoded(&mut test_data).unwrap_err();
+
+    assert!(matches!(err, ReadDecodedError::Decode(DecoderError::Crc32Mismatch(0xCBF4_3926))));
+}
+
+#[test]
+fn decode_to_utf8_string() {
+    // `😀` is split between the third and the fourth decoded block
+    let mut reader = FromBase64Reader::new(OneByteReader(b"Q2Fmw6kg8J+YgA=="));
+
+    let mut test_data = String::from("> ");
+
+    assert_eq!(10, reader.read_to_utf8_string(&mut test_data).unwrap());
+    assert_eq!("> Café 😀", test_data);
+
+    // an invalid byte
+    let mut reader = FromBase64Reader::new(Cursor::new(b"Q2Fm/yA="));
+
+    let mut test_data = String::from("> ");
+
+    let err = reader.read_to_utf8_string(&mut test_data).unwrap_err();
+
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+    assert_eq!("> ", test_data);
+
+    // an incomplete character at the end
+    let mut reader = FromBase64Reader::new(Cursor::new(b"Q2Fmww=="));
+
+    let mut test_data = String::new();
+
+    assert!(reader.read_to_utf8_string(&mut test_data).is_err());
+    assert_eq!("", test_data);
+}
+
+#[test]
+fn decode_take_decoded() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh|rest")).take_decoded(4);
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi t", test_data);
+    assert_eq!(0, reader.limit());
+
+    // the inner reader stops right after the block holding the last byte
+    let (mut inner, leftover) = reader.into_inner();
+
+    assert!(leftover.is_empty());
+
+    let mut rest = String::new();
+
+    inner.read_to_string(&mut rest).unwrap();
+
+    assert_eq!("cmUh|rest", rest);
+
+    // the stream ends before the limit
+    let mut reader = FromBase64Reader::new(OneByteReader(b"SGkgdGhlcmUh")).take_decoded(100);
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there!", test_data);
+    assert_eq!(91, reader.limit());
+}
+
+#[test]
+fn decode_custom_padding() {
+    let mut reader = FromBase64Reader::new_with_padding(OneByteReader(b"SGkgdGhlcmUhIQ.."), b'.').unwrap();
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there!!", test_data);
+
+    let mut reader = FromBase64Reader::new_with_padding(Cursor::new(b"SGkgdGhlcmUhIQ=="), b'.').unwrap();
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+
+    let err = FromBase64Reader::new_with_padding(Cursor::new(b""), b'A').unwrap_err();
+
+    assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+}
+
+#[test]
+fn decode_interrupted() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==";
+
+    let mut reader = FromBase64Reader::new(InterruptingReader {
+        inner: OneByteReader(base64),
+        interrupt: false,
+    });
+
+    // reading with a small buffer leaves decoded bytes and partial blocks behind between reads
+    let mut buf = [0u8; 2];
+    let mut test_data = Vec::new();
+
+    loop {
+        let c = reader.read(&mut buf).unwrap();
+
+        if c == 0 {
+            break;
+        }
+
+        test_data.extend_from_slice(&buf[..c]);
+    }
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+
+    let mut reader = FromBase64Reader::new_ignoring_whitespace(InterruptingReader {
+        inner: OneByteReader(MIXED_LINE_ENDINGS),
+        interrupt: false,
+    });
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+}
+
+#[test]
+fn decode_from_str_and_bytes() {
+    let mut test_data = String::new();
+
+    FromBase64Reader::from("SGVsbG8=").read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hello", test_data);
+
+    let mut test_data = String::new();
+
+    FromBase64Reader::from(&b"SGVsbG8="[..]).read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hello", test_data);
+}
+
+#[test]
+fn decode_debug() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh"));
+
+    let mut test_data = [0u8; 4];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    // only the buffered base64 data and the decoded bytes left behind are shown
+    let debug = format!("{:?}", reader);
+
+    assert!(debug.contains(r#"buf: "636d5568" (4 bytes)"#), "{}", debug);
+    assert!(debug.contains(r#"temp: "6865" (2 bytes)"#), "{}", debug);
+}
+
+#[test]
+fn decode_nul_padded() {
+    let fields: [&[u8]; 3] = [
+        b"SGkgdGhlcmUh",
+        b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVz",
+        b"dGluZyB0aGlzIGNyYXRlLg==",
+    ];
+
+    // records of 64 columns, each padded with NULs
+    let mut records = Vec::new();
+
+    for field in fields {
+        records.extend_from_slice(field);
+        records.resize(records.len() + 64 - field.len(), 0);
+    }
+
+    let mut inner = OneByteReader(&records);
+
+    for expected in [
+        "Hi there!",
+        "Hi there, this is a simple sentence used for tes",
+        "ting this crate.",
+    ] {
+        let mut reader = FromBase64Reader::new_nul_padded((&mut inner).take(64));
+
+        let mut field = String::new();
+
+        reader.read_to_string(&mut field).unwrap();
+
+        assert_eq!(expected, field);
+    }
+
+    assert!(inner.0.is_empty());
+
+    // a NUL before any base64 data
+    let mut reader = FromBase64Reader::new_nul_padded(Cursor::new(b"\0SGkg"));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+
+    // base64 data after the NULs
+    let mut reader = FromBase64Reader::new_nul_padded(OneByteReader(b"SGkg\0\0SGkg"));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_copy_to() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".repeat(1000);
+
+    let base64 = STANDARD.encode(&test_data).into_bytes();
+
+    let mut decoded = Vec::new();
+
+    assert_eq!(test_data.len() as u64, base64_stream::decode_copy(Cursor::new(&base64), &mut decoded).unwrap());
+    assert_eq!(test_data, decoded);
+
+    // the data buffered by `fill_buf` is written first
+    let mut reader = FromBase64Reader::new(OneByteReader(&base64));
+
+    reader.fill_buf().unwrap();
+
+    let mut decoded = Vec::new();
+
+    assert_eq!(test_data.len() as u64, reader.copy_to(&mut decoded).unwrap());
+    assert_eq!(test_data, decoded);
+}
+
+#[test]
+fn decode_recovering() {
+    // the second and the fourth blocks are corrupted
+    let base64 = b"SGkg*GhlcmUs*HRoaXMgaXMgYSBzaW1wbGUgc2VudGVu".to_vec();
+
+    let mut reader = FromBase64Reader::new_recovering(Cursor::new(base64.clone()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi \0\0\0re,\0\0\0is is a simple senten".as_ref(), test_data);
+    assert_eq!(&[4, 12], reader.skipped_windows());
+
+    // one block at a time
+    let mut reader = FromBase64Reader::new_recovering(OneByteReader(&base64));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi \0\0\0re,\0\0\0is is a simple senten".as_ref(), test_data);
+    assert_eq!(&[4, 12], reader.skipped_windows());
+
+    let mut reader = FromBase64Reader::new(Cursor::new(&base64));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    assert!(reader.skipped_windows().is_empty());
+}
+
+#[test]
+fn decode_tiny_reads_random() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+    use base64_stream::generic_array::typenum::U4;
+
+    // xorshift, so that the test is reproducible without extra crates
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+
+    let mut below = |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        (state % n as u64) as usize
+    };
+
+    for _ in 0..500 {
+        let data: Vec<u8> = (0..below(100)).map(|_| below(256) as u8).collect();
+
+        let base64 = STANDARD.encode(&data).into_bytes();
+
+        let mut reader: FromBase64Reader<_, U4> =
+            FromBase64Reader::new2(OneByteReader(&base64), &STANDARD);
+
+        let mut buf = [0u8; 2];
+        let mut test_data = Vec::new();
+
+        loop {
+            let c = reader.read(&mut buf[..(1 + below(2))]).unwrap();
+
+            if c == 0 {
+                break;
+            }
+
+            test_data.extend_from_slice(&buf[..c]);
+        }
+
+        assert_eq!(data, test_data);
+    }
+}
+
+#[test]
+fn decode_ended_cleanly() {
+    for (base64, expected) in [
+        (b"".as_ref(), true),
+        (b"SGkgdGhlcmU=".as_ref(), true),
+        (b"SGkgdGhlcmUh".as_ref(), true),
+        (b"SGkgdGhlcmU".as_ref(), false),
+        (b"SGkgdGhlcmUhSA".as_ref(), false),
+    ] {
+        let mut reader = FromBase64Reader::new_no_pad(Cursor::new(base64));
+
+        assert_eq!(None, reader.ended_cleanly());
+
+        reader.read_to_end(&mut Vec::new()).unwrap();
+
+        assert_eq!(Some(expected), reader.ended_cleanly());
+    }
+
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh"));
+
+    reader.read_exact(&mut [0u8; 9]).unwrap();
+
+    assert_eq!(None, reader.ended_cleanly());
+
+    assert_eq!(0, reader.read(&mut [0u8; 1]).unwrap());
+
+    assert_eq!(Some(true), reader.ended_cleanly());
+}
+
+#[test]
+fn decode_is_eof() {
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh"));
+
+    assert_eq!(0, reader.read(&mut []).unwrap());
+    assert!(!reader.is_eof());
+
+    reader.read_exact(&mut [0u8; 9]).unwrap();
+
+    assert_eq!(0, reader.read(&mut []).unwrap());
+    assert!(!reader.is_eof());
+
+    assert_eq!(0, reader.read(&mut [0u8; 1]).unwrap());
+    assert!(reader.is_eof());
+
+    // the data decoded ahead by `peek` has to be read first
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgdGhlcmUh"));
+
+    assert_eq!(b"Hi there!", reader.peek(10).unwrap());
+    assert!(!reader.is_eof());
+
+    reader.read_to_end(&mut Vec::new()).unwrap();
+
+    assert!(reader.is_eof());
+
+    // a truncated last block which fails to decode does not end the stream
+    for base64 in [b"SGkgdGhlcmUhS".as_ref(), b"SGkgdGhlcmU"] {
+        let mut reader = FromBase64Reader::new(Cursor::new(base64));
+
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+        assert!(!reader.is_eof());
+        assert_eq!(None, reader.ended_cleanly());
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn decode_stats() {
+    use std::time::Duration;
+
+    let base64 = b"SGkgdGhlcmUh".repeat(1000);
+
+    let mut reader = FromBase64Reader::new(Cursor::new(&base64));
+
+    assert_eq!(base64_stream::DecodeStats::default(), reader.stats());
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    let stats = reader.stats();
+
+    assert_eq!(test_data.len() as u64, stats.bytes);
+    assert!(stats.elapsed > Duration::ZERO);
+
+    reader.reset(Cursor::new(&base64));
+
+    assert_eq!(base64_stream::DecodeStats::default(), reader.stats());
+}
+
+#[test]
+fn decode_read_buffered() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::generic_array::typenum::U8;
+
+    let base64 = b"SGkgdGhlcmUh".to_vec();
+
+    let mut reader: FromBase64Reader<_, U8> = FromBase64Reader::new2(Cursor::new(base64), &STANDARD);
+
+    let mut test_data = [0u8; 16];
+
+    // nothing has been read from the inner reader yet
+    assert_eq!(0, reader.read_buffered(&mut test_data).unwrap());
+    assert_eq!(0, reader.consumed_count());
+
+    // the first read decodes one block and leaves the second one buffered
+    assert_eq!(3, reader.read(&mut test_data[..3]).unwrap());
+    assert_eq!(8, reader.consumed_count());
+
+    assert_eq!(3, reader.read_buffered(&mut test_data[3..]).unwrap());
+    assert_eq!(0, reader.read_buffered(&mut test_data[6..]).unwrap());
+    assert_eq!(8, reader.consumed_count());
+
+    assert_eq!(3, reader.read(&mut test_data[6..]).unwrap());
+    assert_eq!(b"Hi there!", &test_data[..9]);
+}
+
+#[test]
+fn decode_non_canonical() {
+    use base64_stream::base64::DecodeError;
+    use base64_stream::FromBase64Error;
+
+    let mut test_data = Vec::new();
+
+    FromBase64Reader::new(Cursor::new(b"SGVsbG8=")).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hello".as_ref(), test_data);
+
+    for result in [
+        FromBase64Reader::new(Cursor::new(b"SGVsbG9=")).read_to_end(&mut Vec::new()),
+        FromBase64Reader::new_no_pad(Cursor::new(b"SGVsbG9")).read_to_end(&mut Vec::new()),
+        FromBase64Reader::new_lenient(Cursor::new(b"SGVs\nbG9=")).read_to_end(&mut Vec::new()),
+    ] {
+        let err = result.unwrap_err();
+
+        let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+        assert!(matches!(err.error(), DecodeError::InvalidLastSymbol(_, b'9')), "{:?}", err);
+    }
+}
+
+#[test]
+fn decode_huge_read_sizes() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::generic_array::typenum::U4;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==";
+    let expected = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.";
+
+    // a read buffer far larger than the decoding buffer
+    let mut reader: FromBase64Reader<_, U4> = FromBase64Reader::new2(Cursor::new(base64), &STANDARD);
+
+    let mut test_data = vec![0u8; 1 << 20];
+    let mut length = 0;
+
+    loop {
+        match reader.read(&mut test_data[length..]).unwrap() {
+            0 => break,
+            c => length += c,
+        }
+    }
+
+    assert_eq!(expected.as_ref(), &test_data[..length]);
+
+    // a limit whose base64 length does not fit in a `u64`
+    let mut test_data = Vec::new();
+
+    FromBase64Reader::new(Cursor::new(base64)).take_decoded(u64::MAX).read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(expected.as_ref(), test_data);
+}
+
+#[test]
+fn decode_resume_after_would_block() {
+    use base64_stream::ReadDecodedError;
+
+    // the chunks end in the middle of blocks
+    let chunks = [b"SGkgdG".as_ref(), b"hlcm", b"Uh"];
+
+    let mut reader =
+        FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let mut test_data = Vec::new();
+
+    let mut would_block = 0;
+
+    loop {
+        let mut buf = [0u8; 16];
+
+        match reader.read_decoded(&mut buf) {
+            Ok(0) => break,
+            Ok(c) => test_data.extend_from_slice(&buf[..c]),
+            Err(ReadDecodedError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                would_block += 1
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
+
+    assert_eq!(b"Hi there!".as_ref(), test_data);
+    assert_eq!(3, would_block);
+
+    // running out of data in the middle of a block is a decode error, not an I/O error
+    let mut reader = FromBase64Reader::new(Cursor::new(b"SGkgd"));
+
+    let mut buf = [0u8; 16];
+
+    assert_eq!(3, reader.read_decoded(&mut buf).unwrap());
+    assert!(matches!(reader.read_decoded(&mut buf), Err(ReadDecodedError::Decode(_))));
+}
+
+#[test]
+fn decode_non_blocking_to_end() {
+    let chunks = [b"SGkgdGhl".as_ref(), b"cmUsIHRoaXMgaXMg", b"YSBzaW1w", b"bGUgc2VudGVuY2Uu"];
+
+    let expected = b"Hi there, this is a simple sentence.".as_ref();
+
+    let mut reader =
+        FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let mut test_data = Vec::new();
+
+    while let Err(e) = reader.read_to_end(&mut test_data) {
+        assert_eq!(std::io::ErrorKind::WouldBlock, e.kind());
+    }
+
+    assert_eq!(expected, test_data);
+
+    let mut reader =
+        FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let mut test_data = Vec::new();
+
+    while let Err(e) = reader.copy_to(&mut test_data) {
+        assert_eq!(std::io::ErrorKind::WouldBlock, e.kind());
+    }
+
+    assert_eq!(expected, test_data);
+}
+
+#[test]
+fn decode_read_until_decoded() {
+    // "header\n" is not a multiple of 3 bytes long, so the rest of its last block is buffered
+    let base64 = b"aGVhZGVyCmJvZHkKZW5k".as_ref();
+
+    let mut reader = FromBase64Reader::new(base64);
+
+    let mut line = Vec::new();
+
+    assert_eq!(7, reader.read_until_decoded(b'\n', &mut line).unwrap());
+    assert_eq!(b"header\n", line.as_slice());
+
+    line.clear();
+
+    assert_eq!(5, reader.read_until_decoded(b'\n', &mut line).unwrap());
+    assert_eq!(b"body\n", line.as_slice());
+
+    // "header\nbody\n" is 4 blocks, so only the rest is left undecoded
+    let (inner, leftover) = reader.into_inner();
+
+    let mut rest = leftover;
+    rest.extend_from_slice(inner);
+
+    assert_eq!(b"ZW5k", rest.as_slice());
+
+    // the data buffered by `fill_buf` is searched first, and the end of the stream is no error
+    let mut reader = FromBase64Reader::new(base64);
+
+    assert_eq!(b"he", &reader.peek(2).unwrap()[..2]);
+
+    line.clear();
+
+    assert_eq!(7, reader.read_until_decoded(b'\n', &mut line).unwrap());
+    assert_eq!(b"header\n", line.as_slice());
+
+    line.clear();
+
+    assert_eq!(8, reader.read_until_decoded(b'!', &mut line).unwrap());
+    assert_eq!(b"body\nend", line.as_slice());
+
+    assert_eq!(0, reader.read_until_decoded(b'!', &mut line).unwrap());
+}
+
+#[test]
+fn decode_try_new() {
+    use base64_stream::base64::DecodeError;
+    use base64_stream::FromBase64Error;
+
+    let mut reader = FromBase64Reader::try_new(b"SGkgdGhlcmUh".as_ref()).unwrap();
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!", test_data.as_slice());
+
+    let err = FromBase64Reader::try_new(b"SGkg,GhlcmUh".as_ref()).unwrap_err();
+
+    let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+    assert_eq!(4, err.offset());
+    assert!(matches!(err.error(), DecodeError::InvalidByte(_, b',')));
+
+    // only the first buffer is checked
+    let mut base64 = b"SGkgdGhlcmUs".repeat(400);
+
+    base64[4100] = b'*';
+
+    let mut reader = FromBase64Reader::try_new(Cursor::new(base64)).unwrap();
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_default() {
+    use base64_stream::generic_array::typenum::U4;
+
+    let mut reader: FromBase64Reader<Cursor<Vec<u8>>> = FromBase64Reader::default();
+
+    assert_eq!(0, reader.read(&mut [0u8; 4]).unwrap());
+
+    reader.reset(Cursor::new(b"SGkgdGhlcmUh".to_vec()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there!", test_data.as_slice());
+
+    let mut reader: FromBase64Reader<std::io::Empty, U4> = FromBase64Reader::default();
+
+    assert_eq!(0, reader.read(&mut [0u8; 4]).unwrap());
+}
+
+#[test]
+fn decode_chunks_across_blocks() {
+    // one base64 string, split at arbitrary bytes rather than at 4-character blocks
+    let chunks = [b"SGkgd".as_ref(), b"Ghl", b"cmUsIHRoa", b"XMgaXMgYSBzaW", b"1wbGUgc2VudGVuY2U="];
+
+    let mut reader = FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let mut test_data = String::new();
+
+    while let Err(e) = reader.read_to_string(&mut test_data) {
+        assert_eq!(std::io::ErrorKind::WouldBlock, e.kind());
+    }
+
+    assert_eq!("Hi there, this is a simple sentence", test_data);
+
+    // padding which ends a chunk in the middle of the string is invalid
+    let chunks = [b"SGk=".as_ref(), b"dGhlcmUh"];
+
+    let mut reader = FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let err = loop {
+        match reader.read_to_end(&mut Vec::new()) {
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
+            result => break result.unwrap_err(),
+        }
+    };
+
+    let err = err.get_ref().unwrap().downcast_ref::<base64_stream::FromBase64Error>().unwrap();
+
+    // the same error as for the data in one chunk
+    assert_eq!(3, err.offset());
+    assert_eq!(&base64_stream::base64::DecodeError::InvalidByte(3, b'='), err.error());
+    assert_eq!(b"SGk=", err.window());
+}
+
+#[test]
+fn decode_padded_block_before_would_block() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::generic_array::typenum::U4;
+
+    let chunks = [b"SGk=".as_ref()];
+
+    let mut reader = FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    let mut test_data = [0u8; 16];
+
+    // the padded block is decoded without waiting for the end of the stream
+    assert_eq!(2, reader.read(&mut test_data).unwrap());
+    assert_eq!(b"Hi", &test_data[..2]);
+    assert_eq!(std::io::ErrorKind::WouldBlock, reader.read(&mut test_data).unwrap_err().kind());
+    assert_eq!(0, reader.read(&mut test_data).unwrap());
+
+    // also with a buffer of one block
+    let mut reader: FromBase64Reader<_, U4> =
+        FromBase64Reader::new2(ChunkedReader { chunks: chunks.iter(), ready: false }, &STANDARD);
+
+    assert_eq!(2, reader.read(&mut test_data).unwrap());
+    assert_eq!(std::io::ErrorKind::WouldBlock, reader.read(&mut test_data).unwrap_err().kind());
+    assert_eq!(0, reader.read(&mut test_data).unwrap());
+}
+
+#[test]
+fn decode_buffered_input() {
+    let chunks = [b"SGkgdGh".as_ref(), b"lcmUh"];
+
+    let mut reader = FromBase64Reader::new(ChunkedReader { chunks: chunks.iter(), ready: false });
+
+    assert_eq!(b"", reader.buffered_input());
+
+    let mut test_data = [0u8; 3];
+
+    assert_eq!(3, reader.read(&mut test_data).unwrap());
+
+    // 3 characters wait for the 4th of their block
+    assert!(reader.read(&mut test_data).is_err());
+    assert_eq!(b"dGh", reader.buffered_input());
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!(b"cmUh", reader.buffered_input());
+}
+
+#[test]
+fn decode_stop_at_padding() {
+    let mut reader = FromBase64Reader::new_stop_at_padding(Cursor::new(b"SGk=CRC32".to_vec()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi".as_ref(), test_data);
+    assert_eq!(Some(true), reader.ended_cleanly());
+    assert_eq!(b"CRC32", reader.buffered_input());
+
+    let (_, leftover) = reader.into_inner();
+
+    assert_eq!(b"CRC32".as_ref(), leftover);
+
+    // the padding is completed by the next read
+    let mut reader =
+        FromBase64Reader::new_stop_at_padding(b"SGkgdA".as_ref().chain(b"==tail".as_ref()));
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi t".as_ref(), test_data);
+    assert_eq!(b"tail", reader.buffered_input());
+
+    // data without padding goes on to the end
+    let mut reader = FromBase64Reader::new_stop_at_padding(b"SGkgdGhlcmUs".as_ref());
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).unwrap();
+
+    assert_eq!(b"Hi there,".as_ref(), test_data);
+
+    // the default still rejects data after the padding
+    let mut reader = FromBase64Reader::new(b"SGk=CRC32".as_ref());
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn decode_boxed() {
+    let mut readers: Vec<_> = ["SGk=", "dGhlcmU="]
+        .iter()
+        .map(|base64| FromBase64Reader::new_boxed(base64.as_bytes()))
+        .collect();
+
+    assert_eq!(std::mem::size_of::<usize>(), std::mem::size_of_val(&readers[0]));
+
+    let mut test_data = String::new();
+
+    for reader in readers.iter_mut() {
+        reader.read_to_string(&mut test_data).unwrap();
+    }
+
+    assert_eq!("Hithere", test_data);
+}
@@ -0,0 +1,101 @@
+use std::io::{Cursor, ErrorKind, Read};
+
+use base64_stream::FromBase64ReaderBuilder;
+
+#[test]
+fn build_combined() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVu\r\nY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlz\r\nIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg\r\n".to_vec();
+
+    let mut reader = FromBase64ReaderBuilder::new()
+        .ignore_whitespace(true)
+        .no_pad(true)
+        .build(Cursor::new(base64))
+        .unwrap();
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", test_data);
+}
+
+#[test]
+fn build_url_safe_with_limit() {
+    let builder = FromBase64ReaderBuilder::new().url_safe(true).limit(4);
+
+    let mut reader = builder.build(Cursor::new(b"-_-_ab8=")).unwrap();
+
+    let mut test_data = [0u8; 4];
+
+    reader.read_exact(&mut test_data).unwrap();
+
+    assert_eq!([0xFB, 0xFF, 0xBF, 0x69], test_data);
+
+    assert!(reader.read(&mut test_data).is_err());
+}
+
+#[test]
+fn build_incompatible() {
+    let err = FromBase64ReaderBuilder::new()
+        .strict(true)
+        .ignore_whitespace(true)
+        .build(Cursor::new(b""))
+        .unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+    let err = FromBase64ReaderBuilder::new()
+        .detect_alphabet(true)
+        .url_safe(true)
+        .build(Cursor::new(b""))
+        .unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+    let err = FromBase64ReaderBuilder::new()
+        .url_safe(true)
+        .padding(b'-')
+        .build(Cursor::new(b""))
+        .unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+    let err = FromBase64ReaderBuilder::new().max_inner_read(0).build(Cursor::new(b"")).unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+    assert!(FromBase64ReaderBuilder::new().strict(true).build(Cursor::new(b"")).is_ok());
+}
+
+#[test]
+fn build_max_inner_read() {
+    /// Record the largest buffer passed to `read`.
+    struct MaxReader<'a>(&'a [u8], usize);
+
+    impl Read for MaxReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.1 = self.1.max(buf.len());
+
+            self.0.read(buf)
+        }
+    }
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2Uu".as_ref();
+
+    let mut reader =
+        FromBase64ReaderBuilder::new().max_inner_read(5).build(MaxReader(base64, 0)).unwrap();
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence.", test_data);
+    assert_eq!(5, reader.get_ref().1);
+
+    // unbounded by default
+    let mut reader = FromBase64ReaderBuilder::new().build(MaxReader(base64, 0)).unwrap();
+
+    reader.read_to_string(&mut String::new()).unwrap();
+
+    assert_eq!(4096, reader.get_ref().1);
+}
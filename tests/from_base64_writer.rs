@@ -23,3 +23,24 @@ fn decode_write() {
 
     assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.", fs::read_to_string(file_path).unwrap());
 }
+
+#[test]
+fn decode_write_finish() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+    let mut writer = FromBase64Writer::new(Vec::new());
+
+    for chunk in base64.chunks(1) {
+        writer.write_all(chunk).unwrap();
+    }
+
+    let test_data = writer.finish().unwrap();
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+
+    let mut writer = FromBase64Writer::new(Vec::new());
+
+    writer.write_all(b"SGkgdGhlcmUhS").unwrap();
+
+    assert!(writer.finish().is_err());
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "futures")]
+
+use base64_stream::futures::{AsyncFromBase64Reader, AsyncToBase64Writer};
+
+use futures::executor::block_on;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, Cursor};
+
+#[test]
+fn async_round_trip() {
+    block_on(async {
+        let test_data = (0..100_000u32).map(|i| (i * 7 + i / 13) as u8).collect::<Vec<u8>>();
+
+        let mut writer = AsyncToBase64Writer::new(Cursor::new(Vec::new()));
+
+        for chunk in test_data.chunks(1234) {
+            writer.write_all(chunk).await.unwrap();
+        }
+
+        writer.close().await.unwrap();
+
+        let base64 = writer.get_ref().get_ref().clone();
+
+        let mut reader = AsyncFromBase64Reader::new(Cursor::new(base64));
+
+        let mut decoded = Vec::new();
+
+        reader.read_to_end(&mut decoded).await.unwrap();
+
+        assert_eq!(test_data, decoded);
+    });
+}
+
+#[test]
+fn async_decode_lines() {
+    block_on(async {
+        let base64 = b"bGluZSAxCmxpbmUgMgpsaW5lIDM=".to_vec();
+
+        let mut reader = AsyncFromBase64Reader::new(Cursor::new(base64));
+
+        let mut line = String::new();
+
+        reader.read_line(&mut line).await.unwrap();
+
+        assert_eq!("line 1\n", line);
+
+        let mut rest = Vec::new();
+
+        reader.read_to_end(&mut rest).await.unwrap();
+
+        assert_eq!(b"line 2\nline 3".to_vec(), rest);
+    });
+}
@@ -0,0 +1,49 @@
+use std::io::{Cursor, Read};
+
+use base64_stream::{Base64ToHexReader, HexToBase64Reader};
+
+#[test]
+fn hex_to_base64() {
+    let hex = b"4869207468657265210A".to_vec();
+
+    let mut reader = HexToBase64Reader::new(Cursor::new(hex));
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!("SGkgdGhlcmUhCg==", base64);
+
+    let mut reader = HexToBase64Reader::new(Cursor::new(b"48692".to_vec()));
+
+    assert!(reader.read_to_string(&mut String::new()).is_err());
+
+    let mut reader = HexToBase64Reader::new(Cursor::new(b"4869zz".to_vec()));
+
+    assert!(reader.read_to_string(&mut String::new()).is_err());
+}
+
+#[test]
+fn base64_to_hex() {
+    let base64 = b"SGkgdGhlcmUhCg==".to_vec();
+
+    let mut reader = Base64ToHexReader::new(Cursor::new(base64.clone()));
+
+    let mut hex = String::new();
+
+    reader.read_to_string(&mut hex).unwrap();
+
+    assert_eq!("4869207468657265210a", hex);
+
+    // a one-byte buffer splits every byte into its two digits
+    let mut reader = Base64ToHexReader::new(Cursor::new(base64));
+
+    let mut hex = Vec::new();
+    let mut buf = [0u8; 1];
+
+    while reader.read(&mut buf).unwrap() > 0 {
+        hex.push(buf[0]);
+    }
+
+    assert_eq!(b"4869207468657265210a".to_vec(), hex);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "parallel")]
+
+use std::fs;
+
+use base64_stream::base64::engine::general_purpose::STANDARD;
+use base64_stream::base64::Engine;
+use base64_stream::{decode_file_parallel, FromBase64Error};
+
+#[test]
+fn decode_parallel() {
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".repeat(10000);
+
+    let file_path = std::env::temp_dir().join("base64_stream_decode_parallel.txt");
+
+    fs::write(&file_path, STANDARD.encode(&test_data) + "\n").unwrap();
+
+    let mut decoded = Vec::new();
+
+    let c = decode_file_parallel(&file_path, &mut decoded).unwrap();
+
+    assert_eq!(test_data.len() as u64, c);
+    assert_eq!(test_data, decoded);
+
+    // whitespaces break the alignment of the chunks
+    fs::write(&file_path, b"SGkgdGhl\nmUh").unwrap();
+
+    let err = decode_file_parallel(&file_path, &mut Vec::new()).unwrap_err();
+
+    assert_eq!(8, err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap().offset());
+
+    // padding may only end the last chunk, and the data is split into a chunk per thread
+    let threads = std::thread::available_parallelism().unwrap().get();
+
+    if threads > 1 {
+        let mut base64 = STANDARD.encode(&test_data).into_bytes();
+
+        let chunk_size = base64.len().div_ceil(threads).next_multiple_of(4);
+
+        base64[chunk_size - 2] = b'=';
+        base64[chunk_size - 1] = b'=';
+
+        fs::write(&file_path, base64).unwrap();
+
+        let err = decode_file_parallel(&file_path, &mut Vec::new()).unwrap_err();
+
+        let err = err.get_ref().unwrap().downcast_ref::<FromBase64Error>().unwrap();
+
+        assert_eq!(chunk_size as u64 - 2, err.offset());
+    }
+
+    fs::remove_file(file_path).unwrap();
+}
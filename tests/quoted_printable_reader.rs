@@ -0,0 +1,81 @@
+use std::io::{Cursor, Read};
+
+use base64_stream::{FromBase64Reader, QuotedPrintableReader};
+
+#[test]
+fn unwrap_quoted_printable() {
+    for (quoted_printable, expected) in [
+        ("", ""),
+        ("SGkgdGhlcmUh", "SGkgdGhlcmUh"),
+        // soft line breaks and escaped padding
+        ("SGkgdGhlc=\r\nmUsIA=3D=\r\n=3D", "SGkgdGhlcmUsIA=="),
+        ("SGkgdGhlc=\nmUsIA=3D=3D", "SGkgdGhlcmUsIA=="),
+        // a soft line break after 2 characters of a block, followed by more data
+        ("SGkgdG=\r\nhlcmU=3D", "SGkgdGhlcmU="),
+        // transport padding after a soft line break, and hard line breaks
+        ("SGkg=  \r\ndGhl\r\ncmU=3D\r\n", "SGkgdGhl\r\ncmU=\r\n"),
+        // unescaped padding
+        ("SGkgdGhlcmU=", "SGkgdGhlcmU="),
+        ("SGkgdGhlcg==", "SGkgdGhlcg=="),
+        ("SGkgdGhlcmU=\r\n", "SGkgdGhlcmU="),
+        ("SGkgdGhlcg=\r\n=\r\n", "SGkgdGhlcg=="),
+        ("SGkgdG=\r\nhlcmU=", "SGkgdGhlcmU="),
+        // a soft line break at the end of the stream
+        ("SGkgdGhl=\r\n", "SGkgdGhl"),
+    ] {
+        let mut reader = QuotedPrintableReader::new(Cursor::new(quoted_printable));
+
+        let mut base64 = String::new();
+
+        reader.read_to_string(&mut base64).unwrap();
+
+        assert_eq!(expected, base64);
+
+        let mut reader = QuotedPrintableReader::new(Cursor::new(quoted_printable));
+
+        let mut base64 = Vec::new();
+
+        let mut byte = [0u8; 1];
+
+        while reader.read(&mut byte).unwrap() > 0 {
+            base64.push(byte[0]);
+        }
+
+        assert_eq!(expected.as_bytes(), base64);
+    }
+}
+
+#[test]
+fn decode_quoted_printable() {
+    // the base64 data of the test sentence encoded by Python's `quopri` and `email.quoprimime`
+    for quoted_printable in [
+        "SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGl=\n\
+         zIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg=3D=3D",
+        "SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGl=\r\n\
+         zIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg=3D=3D",
+    ] {
+        let mut reader = FromBase64Reader::new_quoted_printable(Cursor::new(quoted_printable));
+
+        let mut test_data = String::new();
+
+        reader.read_to_string(&mut test_data).unwrap();
+
+        assert_eq!(
+            "Hi there, this is a simple sentence used for testing this crate. I hope all cases \
+             are correct.",
+            test_data
+        );
+    }
+
+    // wrapped base64 data, whose hard line breaks are skipped
+    let quoted_printable = "SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlz\n\
+                            IGNyYXRlLiBJIGhvcA=3D=3D\n";
+
+    let mut reader = FromBase64Reader::new_quoted_printable(Cursor::new(quoted_printable));
+
+    let mut test_data = String::new();
+
+    reader.read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence used for testing this crate. I hop", test_data);
+}
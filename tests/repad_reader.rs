@@ -0,0 +1,51 @@
+use std::io::{Cursor, Read};
+
+use base64_stream::{FromBase64Reader, RepadReader};
+
+#[test]
+fn repad() {
+    for (base64, expected) in [
+        ("", ""),
+        ("SGkgdGhlcmUh", "SGkgdGhlcmUh"),
+        ("SGkgdGhlcmU", "SGkgdGhlcmU="),
+        ("SGkgdGhlcg", "SGkgdGhlcg=="),
+        ("SGkgdGhlcmU=", "SGkgdGhlcmU="),
+        ("SGkgdGhlcg=", "SGkgdGhlcg=="),
+        ("SGkg\r\ndGhl\r\ncg", "SGkg\r\ndGhl\r\ncg=="),
+    ] {
+        let mut reader = RepadReader::new(Cursor::new(base64));
+
+        let mut padded = String::new();
+
+        reader.read_to_string(&mut padded).unwrap();
+
+        assert_eq!(expected, padded);
+
+        let mut reader = RepadReader::new(Cursor::new(base64));
+
+        let mut padded = Vec::new();
+
+        let mut byte = [0u8; 1];
+
+        while reader.read(&mut byte).unwrap() > 0 {
+            padded.push(byte[0]);
+        }
+
+        assert_eq!(expected.as_bytes(), padded);
+    }
+
+    let mut reader = RepadReader::new(Cursor::new("SGkgd"));
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn repad_for_strict_decoding() {
+    let reader = RepadReader::new(Cursor::new("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2U"));
+
+    let mut test_data = String::new();
+
+    FromBase64Reader::new(reader).read_to_string(&mut test_data).unwrap();
+
+    assert_eq!("Hi there, this is a simple sentence", test_data);
+}
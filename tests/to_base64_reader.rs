@@ -40,3 +40,254 @@ fn encode_read_to_string() {
 
     assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==", base64_string);
 }
+
+#[test]
+fn encode_read_byte_by_byte() {
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec();
+
+    let mut reader = ToBase64Reader::new(Cursor::new(test_data));
+
+    let mut base64 = Vec::new();
+
+    let mut byte = [0u8; 1];
+
+    while reader.read(&mut byte).unwrap() > 0 {
+        base64.push(byte[0]);
+    }
+
+    assert_eq!(b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec(), base64);
+}
+
+#[test]
+fn encode_wrapped() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+
+    let sentence = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.";
+
+    for test_data in [sentence.repeat(10), sentence[..57].to_vec(), Vec::new()] {
+        let expected = STANDARD
+            .encode(&test_data)
+            .as_bytes()
+            .chunks(76)
+            .map(|line| format!("{}\r\n", std::str::from_utf8(line).unwrap()))
+            .collect::<String>();
+
+        let mut reader = ToBase64Reader::new_wrapped(Cursor::new(test_data.clone()), 76, b"\r\n");
+
+        let mut base64 = String::new();
+
+        reader.read_to_string(&mut base64).unwrap();
+
+        assert_eq!(expected, base64);
+
+        let mut reader = ToBase64Reader::new_wrapped(Cursor::new(test_data), 76, b"\r\n");
+
+        let mut base64 = Vec::new();
+
+        let mut byte = [0u8; 1];
+
+        while reader.read(&mut byte).unwrap() > 0 {
+            base64.push(byte[0]);
+        }
+
+        assert_eq!(expected.into_bytes(), base64);
+    }
+}
+
+#[test]
+fn encode_mime() {
+    // the Leviathan passage, wrapped like the output of Python's `base64.encodebytes`
+    let test_data = b"Man is distinguished, not only by his reason, but by this singular passion from other animals, which is a lust of the mind, that by a perseverance of delight in the continued and indefatigable generation of knowledge, exceeds the short vehemence of any carnal pleasure.";
+
+    let expected = "TWFuIGlzIGRpc3Rpbmd1aXNoZWQsIG5vdCBvbmx5IGJ5IGhpcyByZWFzb24sIGJ1dCBieSB0aGlz\r\nIHNpbmd1bGFyIHBhc3Npb24gZnJvbSBvdGhlciBhbmltYWxzLCB3aGljaCBpcyBhIGx1c3Qgb2Yg\r\ndGhlIG1pbmQsIHRoYXQgYnkgYSBwZXJzZXZlcmFuY2Ugb2YgZGVsaWdodCBpbiB0aGUgY29udGlu\r\ndWVkIGFuZCBpbmRlZmF0aWdhYmxlIGdlbmVyYXRpb24gb2Yga25vd2xlZGdlLCBleGNlZWRzIHRo\r\nZSBzaG9ydCB2ZWhlbWVuY2Ugb2YgYW55IGNhcm5hbCBwbGVhc3VyZS4=";
+
+    let mut reader = ToBase64Reader::new_mime(Cursor::new(test_data));
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!(expected, base64);
+
+    let mut reader = ToBase64Reader::new_mime(Cursor::new(test_data));
+
+    let mut base64 = Vec::new();
+
+    let mut byte = [0u8; 1];
+
+    while reader.read(&mut byte).unwrap() > 0 {
+        base64.push(byte[0]);
+    }
+
+    assert_eq!(expected.as_bytes(), base64);
+
+    // the test vectors of RFC 4648, and data filling whole lines, have no line ending at the end
+    for (test_data, expected) in [
+        (b"".as_ref(), ""),
+        (b"f".as_ref(), "Zg=="),
+        (b"fo".as_ref(), "Zm8="),
+        (b"foo".as_ref(), "Zm9v"),
+        (b"foobar".as_ref(), "Zm9vYmFy"),
+        (&test_data[..114], &expected[..154]),
+    ] {
+        let mut reader = ToBase64Reader::new_mime(Cursor::new(test_data));
+
+        let mut base64 = String::new();
+
+        reader.read_to_string(&mut base64).unwrap();
+
+        assert_eq!(expected, base64);
+    }
+}
+
+#[test]
+fn encode_url_safe() {
+    let test_data = vec![0xFB, 0xFF, 0xBE, 0xFB, 0xEF];
+
+    let mut reader = ToBase64Reader::new_with_engine(
+        Cursor::new(test_data.clone()),
+        &base64_stream::base64::engine::general_purpose::URL_SAFE,
+    );
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!("-_----8=", base64);
+
+    let mut reader = ToBase64Reader::new_with_engine(
+        Cursor::new(test_data),
+        &base64_stream::base64::engine::general_purpose::URL_SAFE_NO_PAD,
+    );
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!("-_----8", base64);
+}
+
+#[test]
+fn encoded_len() {
+    use base64_stream::base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+    use base64_stream::base64::Engine;
+
+    for plain_len in 0..10 {
+        let plain = vec![0u8; plain_len];
+
+        assert_eq!(STANDARD.encode(&plain).len(), base64_stream::encoded_len(plain_len, true));
+        assert_eq!(STANDARD_NO_PAD.encode(&plain).len(), base64_stream::encoded_len(plain_len, false));
+    }
+}
+
+#[test]
+fn encode_to_string() {
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref();
+
+    let base64 = base64_stream::encode_to_string(test_data).unwrap();
+
+    assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==", base64);
+}
+
+#[test]
+fn encode_final_group_bytes() {
+    use base64_stream::base64::engine::general_purpose::STANDARD_NO_PAD;
+
+    let mut reader = ToBase64Reader::new_with_engine(Cursor::new(b"Hi there"), &STANDARD_NO_PAD);
+
+    let mut base64 = [0u8; 4];
+
+    reader.read_exact(&mut base64).unwrap();
+
+    assert_eq!(None, reader.final_group_bytes());
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!("dGhlcmU", base64);
+    assert_eq!(Some(2), reader.final_group_bytes());
+
+    let mut reader = ToBase64Reader::new_with_engine(Cursor::new(b"Hi there!"), &STANDARD_NO_PAD);
+
+    reader.read_to_string(&mut String::new()).unwrap();
+
+    assert_eq!(Some(0), reader.final_group_bytes());
+}
+
+#[test]
+fn encode_on_progress() {
+    use std::sync::{Arc, Mutex};
+
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.";
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+
+    let reported = progress.clone();
+
+    let mut reader = ToBase64Reader::new(Cursor::new(test_data))
+        .on_progress(move |count| reported.lock().unwrap().push(count));
+
+    let mut base64 = [0u8; 40];
+
+    reader.read_exact(&mut base64).unwrap();
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    let progress = progress.lock().unwrap();
+
+    assert_eq!(30, progress[0]);
+    assert_eq!(Some(&(test_data.len() as u64)), progress.last());
+    assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+
+    // a callback which is `Send` but not `Sync` is accepted too
+    let calls = std::cell::Cell::new(0);
+
+    let mut reader = ToBase64Reader::new(Cursor::new(test_data)).on_progress(move |_| {
+        calls.set(calls.get() + 1);
+    });
+
+    reader.read_to_end(&mut Vec::new()).unwrap();
+}
+
+#[test]
+fn encode_with_prefix() {
+    let test_data = b"Hi there, this is a simple sentence.".as_ref();
+
+    let mut reader = ToBase64Reader::with_prefix(test_data, "data:text/plain;base64,".to_string());
+
+    let mut data_uri = Vec::new();
+
+    let mut byte = [0u8; 1];
+
+    while reader.read(&mut byte).unwrap() > 0 {
+        data_uri.push(byte[0]);
+    }
+
+    assert_eq!(
+        b"data:text/plain;base64,SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2Uu".as_ref(),
+        data_uri
+    );
+
+    let mut decoded = Vec::new();
+
+    base64_stream::FromBase64Reader::new_data_uri(Cursor::new(data_uri))
+        .read_to_end(&mut decoded)
+        .unwrap();
+
+    assert_eq!(test_data, decoded);
+}
+
+#[test]
+fn encode_default() {
+    let mut reader: ToBase64Reader<Cursor<Vec<u8>>> = ToBase64Reader::default();
+
+    let mut base64 = String::new();
+
+    reader.read_to_string(&mut base64).unwrap();
+
+    assert_eq!("", base64);
+}
@@ -2,7 +2,8 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 
-use base64_stream::ToBase64Writer;
+use base64_stream::{ToBase64StringWriter, ToBase64Writer};
+use base64_stream::generic_array::typenum::U4;
 
 const DATA_FOLDER: &str = "data";
 const ENCODE_OUTPUT: &str = "encode_output.txt";
@@ -23,3 +24,142 @@ fn encode_write() {
 
     assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==", fs::read_to_string(file_path).unwrap());
 }
+
+#[test]
+fn encode_finish() {
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref();
+
+    let mut writer = ToBase64Writer::new(Vec::new());
+
+    writer.write_all(test_data).unwrap();
+
+    let base64 = writer.finish().unwrap();
+
+    assert_eq!(b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec(), base64);
+}
+
+#[test]
+fn encode_drop() {
+    let mut base64 = Vec::new();
+
+    let mut writer = ToBase64Writer::new(&mut base64);
+
+    writer.write_all(b"Hi th").unwrap();
+
+    drop(writer);
+
+    assert_eq!(b"SGkgdGg=".to_vec(), base64);
+}
+
+#[test]
+fn encode_finish_failed() {
+    /// Accept 6 bytes, then fail.
+    struct FailingWriter(Vec<u8>);
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.0.len() >= 6 {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+
+            let length = buf.len().min(6 - self.0.len());
+
+            self.0.extend_from_slice(&buf[..length]);
+
+            Ok(length)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut base64 = FailingWriter(Vec::new());
+
+    let mut writer = ToBase64Writer::new(&mut base64);
+
+    writer.write_all(b"Hi th").unwrap();
+
+    // the error of `finish` is not followed by another write when the writer is dropped
+    assert!(writer.finish().is_err());
+
+    assert_eq!(b"SGkgdG".to_vec(), base64.0);
+}
+
+#[test]
+fn encode_flush_threshold() {
+    use std::cell::Cell;
+
+    /// Count the write calls.
+    struct CountingWriter<'a>(Vec<u8>, &'a Cell<usize>);
+
+    impl Write for CountingWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.1.set(self.1.get() + 1);
+
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref();
+
+    let count = Cell::new(0);
+
+    let mut writer = ToBase64Writer::with_flush_threshold(CountingWriter(Vec::new(), &count), 64);
+
+    for b in test_data.chunks(1) {
+        writer.write_all(b).unwrap();
+    }
+
+    let base64 = writer.finish().unwrap();
+
+    assert_eq!(b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec(), base64.0);
+
+    // 64 bytes are written once the threshold is reached, the remaining 64 bytes by `finish`
+    assert_eq!(2, count.get());
+
+    // nothing is written below the threshold until `flush`
+    let count = Cell::new(0);
+
+    let mut writer = ToBase64Writer::with_flush_threshold(CountingWriter(Vec::new(), &count), 64);
+
+    writer.write_all(b"Hi there!").unwrap();
+
+    assert_eq!(0, count.get());
+
+    writer.flush().unwrap();
+
+    assert_eq!(1, count.get());
+}
+
+#[test]
+fn encode_to_string_writer() {
+    let test_data = b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".as_ref();
+
+    let expected = "SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==";
+
+    let mut writer = ToBase64StringWriter::new();
+
+    writer.write_all(test_data).unwrap();
+
+    assert_eq!(&expected[..124], writer.as_str());
+
+    assert_eq!(expected, writer.into_string());
+
+    for size in 1..=7 {
+        let mut writer: ToBase64StringWriter<U4> =
+            ToBase64StringWriter::new2(&base64::engine::general_purpose::STANDARD);
+
+        for chunk in test_data.chunks(size) {
+            writer.write_all(chunk).unwrap();
+        }
+
+        assert_eq!(expected, writer.into_string());
+    }
+
+    assert_eq!("", ToBase64StringWriter::new().into_string());
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "tokio")]
+
+use base64_stream::tokio::{AsyncFromBase64Reader, AsyncToBase64Writer};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn async_decode_to_end() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+    let mut reader = AsyncFromBase64Reader::new(base64);
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).await.unwrap();
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[tokio::test]
+async fn async_decode_pending() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".as_ref();
+
+    let (mut client, server) = tokio::io::duplex(3);
+
+    let writer = tokio::spawn(async move {
+        for chunk in base64.chunks(5) {
+            client.write_all(chunk).await.unwrap();
+        }
+    });
+
+    let mut reader = AsyncFromBase64Reader::new(server);
+
+    let mut test_data = Vec::new();
+
+    reader.read_to_end(&mut test_data).await.unwrap();
+
+    writer.await.unwrap();
+
+    assert_eq!(b"Hi there, this is a simple sentence used for testing this crate. I hope all cases are correct.".to_vec(), test_data);
+}
+
+#[tokio::test]
+async fn async_encode_round_trip() {
+    let test_data = (0..100_000u32).map(|i| (i * 7 + i / 13) as u8).collect::<Vec<u8>>();
+
+    let (client, server) = tokio::io::duplex(1000);
+
+    let expected = test_data.clone();
+
+    let writer = tokio::spawn(async move {
+        let mut writer = AsyncToBase64Writer::new(client);
+
+        for chunk in test_data.chunks(1234) {
+            writer.write_all(chunk).await.unwrap();
+        }
+
+        writer.shutdown().await.unwrap();
+    });
+
+    let mut reader = AsyncFromBase64Reader::new(server);
+
+    let mut decoded = Vec::new();
+
+    reader.read_to_end(&mut decoded).await.unwrap();
+
+    writer.await.unwrap();
+
+    assert_eq!(expected, decoded);
+}
+
+#[tokio::test]
+async fn async_decode_lines() {
+    use base64_stream::base64::engine::general_purpose::STANDARD;
+    use base64_stream::base64::Engine;
+
+    let records = (0..100).map(|i| format!("{{\"id\":{},\"name\":\"record {}\"}}", i, i)).collect::<Vec<String>>();
+
+    let base64 = STANDARD.encode(records.join("\n"));
+
+    let (mut client, server) = tokio::io::duplex(64);
+
+    let writer = tokio::spawn(async move {
+        for chunk in base64.as_bytes().chunks(7) {
+            client.write_all(chunk).await.unwrap();
+        }
+    });
+
+    let mut lines = AsyncFromBase64Reader::new(server).lines();
+
+    let mut test_records = Vec::new();
+
+    while let Some(line) = lines.next_line().await.unwrap() {
+        test_records.push(line);
+    }
+
+    writer.await.unwrap();
+
+    assert_eq!(records, test_records);
+}
@@ -0,0 +1,75 @@
+use std::io::{Cursor, Read};
+
+use base64_stream::TranscodeReader;
+
+#[test]
+fn transcode_to_url_safe() {
+    let base64 = b"+/+/ab8=".to_vec();
+
+    let mut reader = TranscodeReader::new(
+        Cursor::new(base64),
+        &base64::engine::general_purpose::STANDARD,
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+    );
+
+    let mut url_safe = String::new();
+
+    reader.read_to_string(&mut url_safe).unwrap();
+
+    assert_eq!("-_-_ab8", url_safe);
+}
+
+#[test]
+fn transcode_to_standard() {
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg".to_vec();
+
+    let mut reader = TranscodeReader::new(
+        Cursor::new(base64),
+        &base64::engine::general_purpose::STANDARD_NO_PAD,
+        &base64::engine::general_purpose::STANDARD,
+    );
+
+    let mut standard = String::new();
+
+    reader.read_to_string(&mut standard).unwrap();
+
+    assert_eq!("SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==", standard);
+}
+
+#[test]
+fn transcode_small_buffers() {
+    use base64_stream::generic_array::typenum::U4;
+
+    let base64 = b"SGkgdGhlcmUsIHRoaXMgaXMgYSBzaW1wbGUgc2VudGVuY2UgdXNlZCBmb3IgdGVzdGluZyB0aGlzIGNyYXRlLiBJIGhvcGUgYWxsIGNhc2VzIGFyZSBjb3JyZWN0Lg==".to_vec();
+
+    let mut reader: TranscodeReader<_, U4> = TranscodeReader::new2(
+        Cursor::new(base64.clone()),
+        &base64::engine::general_purpose::STANDARD,
+        &base64::engine::general_purpose::STANDARD,
+    );
+
+    let mut standard = Vec::new();
+
+    let mut test_data = [0u8; 3];
+
+    loop {
+        let c = reader.read(&mut test_data).unwrap();
+
+        if c == 0 {
+            break;
+        }
+
+        standard.extend_from_slice(&test_data[..c]);
+    }
+
+    assert_eq!(base64, standard);
+
+    // invalid base64 data are rejected like by `FromBase64Reader`
+    let mut reader = TranscodeReader::new(
+        Cursor::new(b"SGkg*Ghl".to_vec()),
+        &base64::engine::general_purpose::STANDARD,
+        &base64::engine::general_purpose::URL_SAFE,
+    );
+
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}